@@ -0,0 +1,98 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+const BACKUP_INDEX_FILENAME: &str = "index.json";
+
+// Backups are kept per GMod install, keyed by the install's canonical path
+// They live outside of the cache directory so --disable-cache doesn't wipe them
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BackupIndex {
+	pub gmod_path: String,
+	// Relative filename => blake3 hash of the backed up file
+	pub files: IndexMap<String, String>
+}
+
+pub fn get_backup_dir(gmod_path: &Path) -> PathBuf {
+	let data_dir = if let Some(dirs_data_dir) = dirs::data_local_dir() { dirs_data_dir } else { std::env::temp_dir() };
+	let install_hash = blake3::hash(gmod_path.to_string_lossy().as_bytes()).to_hex();
+
+	extend_pathbuf_and_return(data_dir, &["GModPatchTool", "backups", &install_hash[..16]])
+}
+
+pub fn get_backup_file_path(backup_dir: &Path, filename: &str) -> PathBuf {
+	let file_parts: Vec<&str> = filename.split("/").collect();
+
+	extend_pathbuf_and_return(extend_pathbuf_and_return(backup_dir.to_path_buf(), &["files"]), &file_parts[..])
+}
+
+pub fn read_backup_index(backup_dir: &Path, gmod_path: &Path) -> BackupIndex {
+	let index_path = extend_pathbuf_and_return(backup_dir.to_path_buf(), &[BACKUP_INDEX_FILENAME]);
+
+	match std::fs::read_to_string(index_path) {
+		Ok(index_str) => serde_json::from_str(&index_str).unwrap_or_default(),
+		Err(_) => BackupIndex {
+			gmod_path: gmod_path.to_string_lossy().to_string(),
+			files: IndexMap::new()
+		}
+	}
+}
+
+pub fn write_backup_index(backup_dir: &Path, index: &BackupIndex) -> Result<(), String> {
+	std::fs::create_dir_all(backup_dir).map_err(|error| error.to_string())?;
+
+	let index_path = extend_pathbuf_and_return(backup_dir.to_path_buf(), &[BACKUP_INDEX_FILENAME]);
+	let index_str = serde_json::to_string_pretty(index).map_err(|error| error.to_string())?;
+
+	std::fs::write(index_path, index_str).map_err(|error| error.to_string())
+}
+
+pub enum BackupResult {
+	// Copied into the backup directory
+	BackedUp,
+	// An earlier run already backed up the original, so that's kept as is
+	AlreadyBackedUp,
+	// Not the original (e.g. one we patched with older patches), so it isn't backed up
+	NotOriginal,
+	// The file doesn't exist
+	Missing
+}
+
+// Whether the backup of a file is the original (unpatched) one
+pub fn has_original_backup(backup_dir: &Path, index: &BackupIndex, filename: &str, original_hash: &str) -> bool {
+	index.files.get(filename).is_some_and(|backup_hash| backup_hash == original_hash)
+		&& get_file_hash(&get_backup_file_path(backup_dir, filename)).is_ok_and(|backup_hash| backup_hash == original_hash)
+}
+
+// Copies a GMod file into the backup directory, but only if it's the original
+// Anything else would replace the backup of the original, which is the one thing we can't get back
+pub fn backup_file(backup_dir: &Path, gmod_path: &Path, index: &BackupIndex, filename: &str, original_hash: &str) -> Result<BackupResult, String> {
+	let file_parts: Vec<&str> = filename.split("/").collect();
+	let gmod_file_path = extend_pathbuf_and_return(gmod_path.to_path_buf(), &file_parts[..]);
+
+	if !gmod_file_path.is_file() {
+		return Ok(BackupResult::Missing);
+	}
+
+	if get_file_hash(&gmod_file_path)? != original_hash {
+		return Ok(BackupResult::NotOriginal);
+	}
+
+	if has_original_backup(backup_dir, index, filename, original_hash) {
+		return Ok(BackupResult::AlreadyBackedUp);
+	}
+
+	let backup_file_path = get_backup_file_path(backup_dir, filename);
+	if let Some(backup_file_path_dir) = backup_file_path.parent() {
+		std::fs::create_dir_all(backup_file_path_dir).map_err(|error| error.to_string())?;
+	}
+
+	std::fs::copy(&gmod_file_path, &backup_file_path).map_err(|error| error.to_string())?;
+
+	// Make sure it didn't change while we were copying it
+	if get_file_hash(&backup_file_path)? != original_hash {
+		let _ = std::fs::remove_file(&backup_file_path);
+		return Err("File changed while it was being backed up".to_string());
+	}
+
+	Ok(BackupResult::BackedUp)
+}
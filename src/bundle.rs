@@ -0,0 +1,143 @@
+use crate::*;
+use std::fs::File;
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, SeekFrom};
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+// A local copy of the files branch (version.txt, manifest.json, patches/, originals/) for machines without internet access
+// Archives (.tar or .tar.zst) hold every platform and branch, so only what's asked for is unpacked (see extract)
+// They're unpacked next to our cache dir, which is removed when the bundle is dropped
+pub struct OfflineBundle {
+	pub dir: PathBuf,
+	archive_path: Option<PathBuf>,
+	unpack_dir: Option<PathBuf>
+}
+
+impl OfflineBundle {
+	pub fn open(bundle_path: &Path, unpack_parent_dir: &Path) -> Result<OfflineBundle, String> {
+		if bundle_path.is_dir() {
+			return Ok(OfflineBundle {
+				dir: find_bundle_root(bundle_path.to_path_buf()),
+				archive_path: None,
+				unpack_dir: None
+			});
+		}
+
+		if !bundle_path.is_file() {
+			return Err(format!("Offline bundle not found: {}", bundle_path.display()));
+		}
+
+		let unpack_dir = extend_pathbuf_and_return(unpack_parent_dir.to_path_buf(), &[format!("GModPatchTool-bundle-{}", std::process::id()).as_str()]);
+		let _ = std::fs::remove_dir_all(&unpack_dir);
+
+		// Set up the cleanup before unpacking, so a failed unpack doesn't leave anything behind
+		let mut bundle = OfflineBundle {
+			dir: unpack_dir.clone(),
+			archive_path: Some(bundle_path.to_path_buf()),
+			unpack_dir: Some(unpack_dir.clone())
+		};
+
+		// Just the top-level files (manifest.json, etc) for now, which may be inside a single top-level dir
+		std::fs::create_dir_all(&unpack_dir).map_err(|error| error.to_string())?;
+		unpack_bundle_archive(bundle_path, &unpack_dir, |entry_path| {
+			let mut entry_path_parts = entry_path.split("/").filter(|part| !part.is_empty() && *part != ".");
+			let first_part = entry_path_parts.next();
+
+			match (entry_path_parts.next(), entry_path_parts.next()) {
+				(None, _) => true,
+				(Some(_), None) => !matches!(first_part, Some("patches" | "originals" | "symbols")),
+				_ => false
+			}
+		}).map_err(|error| format!("Couldn't unpack offline bundle: {error}"))?;
+
+		bundle.dir = find_bundle_root(unpack_dir);
+
+		Ok(bundle)
+	}
+
+	// Unpacks files (relative to the bundle root, e.g. patches/linux/x86-64/bin/foo.so.bsdiff) from an archive, so they can be read from dir
+	// Does nothing for directories, where everything is already there
+	pub fn extract(&self, filenames: &[String]) -> Result<(), String> {
+		let (Some(archive_path), Some(unpack_dir)) = (&self.archive_path, &self.unpack_dir) else {
+			return Ok(());
+		};
+
+		if filenames.is_empty() {
+			return Ok(());
+		}
+
+		// e.g. "gmodpatchtool-files/" if the archive was made from the parent dir
+		let root_prefix = self.dir.strip_prefix(unpack_dir).ok()
+			.map(|root_dir| root_dir.iter().map(|part| format!("{}/", part.to_string_lossy())).collect::<String>())
+			.unwrap_or_default();
+
+		let filenames: HashSet<&str> = filenames.iter().map(String::as_str).collect();
+
+		unpack_bundle_archive(archive_path, unpack_dir, |entry_path| {
+			entry_path.trim_start_matches("./").strip_prefix(root_prefix.as_str()).is_some_and(|filename| filenames.contains(filename))
+		}).map_err(|error| format!("Couldn't unpack files from offline bundle: {error}"))
+	}
+}
+
+impl Drop for OfflineBundle {
+	fn drop(&mut self) {
+		if let Some(unpack_dir) = &self.unpack_dir {
+			let _ = std::fs::remove_dir_all(unpack_dir);
+		}
+	}
+}
+
+// Unpacks the archive's files that wanted says yes to (by their path in the archive)
+fn unpack_bundle_archive(archive_path: &Path, unpack_dir: &Path, wanted: impl Fn(&str) -> bool) -> io::Result<()> {
+	let mut archive_file = File::open(archive_path)?;
+
+	// Detect Zstandard by its magic number instead of trusting the extension
+	let mut magic = [0u8; 4];
+	let is_zstd = archive_file.read_exact(&mut magic).is_ok() && magic == ZSTD_MAGIC;
+	archive_file.seek(SeekFrom::Start(0))?;
+
+	if is_zstd {
+		unpack_bundle_entries(tar::Archive::new(zstd::stream::read::Decoder::new(archive_file)?), unpack_dir, wanted)
+	} else {
+		unpack_bundle_entries(tar::Archive::new(archive_file), unpack_dir, wanted)
+	}
+}
+
+fn unpack_bundle_entries<R: Read>(mut archive: tar::Archive<R>, unpack_dir: &Path, wanted: impl Fn(&str) -> bool) -> io::Result<()> {
+	for entry in archive.entries()? {
+		let mut entry = entry?;
+		if !entry.header().entry_type().is_file() {
+			continue;
+		}
+
+		let entry_path = entry.path()?.to_string_lossy().replace("\\", "/");
+		if wanted(&entry_path) {
+			// tar refuses to unpack entries outside of unpack_dir (../, absolute paths)
+			entry.unpack_in(unpack_dir)?;
+		}
+	}
+
+	Ok(())
+}
+
+// Archives are often made from the parent dir, so allow the files to be inside a single top-level dir
+fn find_bundle_root(dir: PathBuf) -> PathBuf {
+	if dir.join("manifest.json").is_file() {
+		return dir;
+	}
+
+	let subdirs: Vec<PathBuf> = match dir.read_dir() {
+		Ok(read_dir) => read_dir.filter_map(|entry| entry.ok())
+			.map(|entry| entry.path())
+			.filter(|path| path.is_dir())
+			.collect(),
+		Err(_) => Vec::new()
+	};
+
+	if subdirs.len() == 1 && subdirs[0].join("manifest.json").is_file() {
+		subdirs[0].clone()
+	} else {
+		dir
+	}
+}
@@ -0,0 +1,36 @@
+use crate::*;
+use serde::Deserialize;
+
+const CONFIG_FILENAME: &str = "config.json";
+
+// Optional user config, e.g. for pointing the tool at a self-hosted mirror
+// Everything in here can also be set from the command line, which takes priority
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+	// Mirror roots in priority order (http://, https://, or file://)
+	pub mirrors: Vec<String>,
+	// Whether to fall back to the built-in mirrors after the ones above
+	pub use_default_mirrors: Option<bool>
+}
+
+pub fn get_default_config_path() -> Option<PathBuf> {
+	dirs::config_dir().map(|config_dir| extend_pathbuf_and_return(config_dir, &["GModPatchTool", CONFIG_FILENAME]))
+}
+
+// A missing config file is only an error if it was explicitly asked for
+pub fn read_config(config_path: Option<&Path>) -> Result<Config, String> {
+	let (config_path, explicit) = match config_path {
+		Some(config_path) => (config_path.to_path_buf(), true),
+		None => match get_default_config_path() {
+			Some(config_path) => (config_path, false),
+			None => return Ok(Config::default())
+		}
+	};
+
+	match std::fs::read_to_string(&config_path) {
+		Ok(config_str) => serde_json::from_str(&config_str).map_err(|error| format!("Couldn't parse config file ({}): {error}", config_path.display())),
+		Err(error) if !explicit && error.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+		Err(error) => Err(format!("Couldn't read config file ({}): {error}", config_path.display()))
+	}
+}
@@ -0,0 +1,343 @@
+use crate::*;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+const LOCAL_CHUNK_SIZE: usize = 0x40000;
+
+// Enough to keep a typical connection busy without tripping the mirrors' anti-DDoS
+pub const DEFAULT_MAX_PARALLEL_DOWNLOADS: u16 = 4;
+
+// Try each mirror this many times for transient errors (Anti-DDoS, dropped connections, etc)
+pub const MAX_TRIES_PER_MIRROR: u8 = 3;
+
+// Retries back off exponentially from here, up to the max
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+// If a mirror wants us to wait longer than this (Retry-After), we'd rather try the next one
+const RETRY_AFTER_MAX: Duration = Duration::from_secs(60);
+
+// Every request gets this long, plus however long its size takes at the minimum speed
+// Past that, the download is too slow and we try again (resuming where we left off)
+const DEADLINE_BASE: Duration = Duration::from_secs(30);
+const DEADLINE_MIN_BYTES_PER_SEC: u64 = 0x10000;
+
+// Partially downloaded files are kept (still compressed) until they're complete, so they can be resumed
+pub const PART_FILE_SUFFIX: &str = ".part";
+
+// Shared between all downloads, so the limits apply to all of them together
+pub struct Downloader {
+	pub client: reqwest::Client,
+	max_parallel_downloads: u16,
+	download_slots: Semaphore,
+	rate_limiter: Option<RateLimiter>
+}
+
+impl Downloader {
+	pub fn new(max_parallel_downloads: u16, limit_rate: Option<u64>) -> Result<Downloader, reqwest::Error> {
+		let client = reqwest::Client::builder()
+			.connect_timeout(Duration::new(10, 0)) // Initial connection failure
+			.read_timeout(Duration::new(10, 0)) // Stall detection
+			.build()?;
+
+		let max_parallel_downloads = max_parallel_downloads.max(1);
+
+		Ok(Downloader {
+			client,
+			max_parallel_downloads,
+			download_slots: Semaphore::new(max_parallel_downloads as usize),
+			rate_limiter: limit_rate.map(RateLimiter::new)
+		})
+	}
+
+	// Total time a request can take, scaled to how much is left to download
+	// None if we don't know the size, in which case only stalls time out
+	pub fn get_deadline(&self, remaining_size: Option<u64>) -> Option<Duration> {
+		// With --limit-rate, each download only gets its share of it
+		let min_bytes_per_sec = match &self.rate_limiter {
+			Some(rate_limiter) => (rate_limiter.bytes_per_sec / self.max_parallel_downloads as u64).clamp(1, DEADLINE_MIN_BYTES_PER_SEC),
+			None => DEADLINE_MIN_BYTES_PER_SEC
+		};
+
+		remaining_size.map(|remaining_size| DEADLINE_BASE + Duration::from_secs(remaining_size / min_bytes_per_sec))
+	}
+
+	// Waits until fewer than --max-parallel-downloads files are downloading
+	pub async fn acquire_download_slot(&self) -> SemaphorePermit<'_> {
+		// We never close the semaphore
+		self.download_slots.acquire().await.unwrap()
+	}
+
+	// Call after receiving each chunk; waits as long as needed to stay under --limit-rate
+	pub async fn throttle(&self, bytes: usize) {
+		if let Some(rate_limiter) = &self.rate_limiter {
+			rate_limiter.throttle(bytes as u64).await;
+		}
+	}
+}
+
+// Token bucket shared by every download, refilled at bytes_per_sec
+// Going over puts it into debt, which whoever went over waits off
+struct RateLimiter {
+	bytes_per_sec: u64,
+	// (allowance, last refill)
+	state: Mutex<(f64, Instant)>
+}
+
+impl RateLimiter {
+	fn new(bytes_per_sec: u64) -> RateLimiter {
+		RateLimiter {
+			bytes_per_sec: bytes_per_sec.max(1),
+			state: Mutex::new((0.0, Instant::now()))
+		}
+	}
+
+	async fn throttle(&self, bytes: u64) {
+		let bytes_per_sec = self.bytes_per_sec as f64;
+
+		let wait = {
+			let mut state = self.state.lock().unwrap();
+			let now = Instant::now();
+
+			// Allow up to a second of burst
+			state.0 = (state.0 + now.duration_since(state.1).as_secs_f64() * bytes_per_sec).min(bytes_per_sec);
+			state.1 = now;
+			state.0 -= bytes as f64;
+
+			if state.0 < 0.0 { Duration::from_secs_f64(-state.0 / bytes_per_sec) } else { Duration::ZERO }
+		};
+
+		if !wait.is_zero() {
+			tokio::time::sleep(wait).await;
+		}
+	}
+}
+
+pub enum RetryDecision {
+	Retry(Duration),
+	NextMirror
+}
+
+// try_count is how many times the current mirror has failed, including this time
+// Permanent errors (404, etc) won't go away by retrying, so we move on right away
+pub fn get_retry_decision(try_count: u8, transient: bool, retry_after: Option<Duration>) -> RetryDecision {
+	if !transient || try_count >= MAX_TRIES_PER_MIRROR {
+		return RetryDecision::NextMirror;
+	}
+
+	match retry_after {
+		Some(retry_after) if retry_after > RETRY_AFTER_MAX => RetryDecision::NextMirror,
+		Some(retry_after) => RetryDecision::Retry(retry_after),
+		None => {
+			let delay = RETRY_BASE_DELAY.saturating_mul(1 << try_count.saturating_sub(1).min(16)).min(RETRY_MAX_DELAY);
+
+			// Up to 50% jitter, so parallel downloads don't all hit the mirror again at the same time
+			let jitter_ms = fastrand::u64(0..=delay.as_millis() as u64 / 2);
+
+			RetryDecision::Retry(delay + Duration::from_millis(jitter_ms))
+		}
+	}
+}
+
+// Rate limits, overload, and gateway errors are worth retrying; everything else isn't going to change
+pub fn is_transient_http_status(status_code: u16) -> bool {
+	matches!(status_code, 408 | 425 | 429 | 500 | 502 | 503 | 504)
+}
+
+// Timeouts, and network problems while connecting or sending/receiving, are worth retrying
+// Everything else (TLS/certificate problems, bad responses, redirect loops, etc) isn't going to change, so move on to the next mirror
+pub fn is_transient_http_error(error: &reqwest::Error) -> bool {
+	if error.is_timeout() {
+		return true;
+	}
+
+	if !error.is_connect() && !error.is_request() && !error.is_body() {
+		return false;
+	}
+
+	// TLS and certificate errors also come up as IO errors, but with invalid data
+	get_io_error(error).is_some_and(|io_error| io_error.kind() != io::ErrorKind::InvalidData)
+}
+
+// The innermost IO error underneath a reqwest error, if there is one
+// hyper wraps rustls' InvalidData errors in another IO error, and IO errors skip their inner error in source()
+fn get_io_error(error: &reqwest::Error) -> Option<&io::Error> {
+	let mut source = std::error::Error::source(error);
+	let mut io_error = None;
+
+	while let Some(error) = source {
+		if let Some(inner_io_error) = error.downcast_ref::<io::Error>() {
+			io_error = Some(inner_io_error);
+			source = inner_io_error.get_ref().map(|error| error as &(dyn std::error::Error + 'static));
+		} else {
+			source = error.source();
+		}
+	}
+
+	io_error
+}
+
+// Only the delay-seconds form; HTTP dates fall back to our own backoff
+pub fn get_retry_after(response: &reqwest::Response) -> Option<Duration> {
+	let retry_after = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+
+	retry_after.trim().parse().ok().map(Duration::from_secs)
+}
+
+// For --limit-rate: bytes per second, with an optional K/M/G suffix (e.g. 500K, 2.5M)
+pub fn parse_rate(rate: &str) -> Result<u64, String> {
+	let rate_trimmed = rate.trim().trim_end_matches("/s").trim_end_matches(['B', 'b']).trim_end_matches('i');
+	let (number, multiplier) = match rate_trimmed.chars().last().map(|suffix| suffix.to_ascii_uppercase()) {
+		Some('K') => (&rate_trimmed[..rate_trimmed.len() - 1], 0x400),
+		Some('M') => (&rate_trimmed[..rate_trimmed.len() - 1], 0x100000),
+		Some('G') => (&rate_trimmed[..rate_trimmed.len() - 1], 0x40000000),
+		_ => (rate_trimmed, 1)
+	};
+
+	match number.trim().parse::<f64>() {
+		Ok(number) if number > 0.0 => Ok((number * multiplier as f64) as u64),
+		_ => Err(format!("Invalid rate: {rate} (expected e.g. 500K or 2M)"))
+	}
+}
+
+// An open download from a mirror, either an HTTP response body or a local file
+pub enum FileStream {
+	Http(reqwest::Response),
+	Local(tokio::fs::File)
+}
+
+impl FileStream {
+	// Returns None once the whole file has been read
+	pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, String> {
+		match self {
+			FileStream::Http(response) => match response.chunk().await {
+				Ok(chunk) => Ok(chunk.map(|chunk| chunk.to_vec())),
+				Err(error) => Err(error.without_url().to_string())
+			},
+			FileStream::Local(file) => {
+				let mut chunk = vec![0u8; LOCAL_CHUNK_SIZE];
+				let chunk_len = file.read(&mut chunk).await.map_err(|error| error.to_string())?;
+
+				if chunk_len == 0 {
+					return Ok(None);
+				}
+
+				chunk.truncate(chunk_len);
+				Ok(Some(chunk))
+			}
+		}
+	}
+}
+
+// Returns the file, where it starts, and its full size
+// Starts over if the offset is past the end of the file, since that means our partial file isn't from this one
+pub async fn open_local_file(file_path: &str, offset: u64) -> io::Result<(tokio::fs::File, u64, u64)> {
+	let mut file = tokio::fs::File::open(file_path).await?;
+	let size = file.metadata().await?.len();
+	let offset = if offset <= size { offset } else { 0 };
+
+	file.seek(io::SeekFrom::Start(offset)).await?;
+
+	Ok((file, offset, size))
+}
+
+// Content-Range: bytes <start>-<end>/<size or *>
+pub fn parse_content_range(content_range: &str) -> Option<(u64, Option<u64>)> {
+	let (range, size) = content_range.strip_prefix("bytes ")?.split_once("/")?;
+	let (start, _) = range.split_once("-")?;
+
+	Some((start.parse().ok()?, size.parse().ok()))
+}
+
+// Hashes everything written to it, and optionally writes it to a file too
+pub struct HashingWriter {
+	hasher: blake3::Hasher,
+	file: Option<BufWriter<File>>
+}
+
+impl Write for HashingWriter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if let Some(file) = &mut self.file {
+			file.write_all(buf)?;
+		}
+
+		self.hasher.update(buf);
+
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match &mut self.file {
+			Some(file) => file.flush(),
+			None => Ok(())
+		}
+	}
+}
+
+// Where the downloaded bytes go, as they come in
+// Zstandard files are decompressed into output_path; other files are already complete in the .part file, so they're only hashed
+pub enum CacheFileSink {
+	Zstd(zstd::stream::write::Decoder<'static, HashingWriter>),
+	Plain(HashingWriter)
+}
+
+impl CacheFileSink {
+	pub fn new(output_path: Option<&Path>) -> io::Result<CacheFileSink> {
+		match output_path {
+			Some(output_path) => {
+				let writer = HashingWriter {
+					hasher: blake3::Hasher::new(),
+					file: Some(BufWriter::new(File::create(output_path)?))
+				};
+
+				Ok(CacheFileSink::Zstd(zstd::stream::write::Decoder::new(writer)?))
+			},
+			None => Ok(CacheFileSink::Plain(HashingWriter {
+				hasher: blake3::Hasher::new(),
+				file: None
+			}))
+		}
+	}
+
+	pub fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+		match self {
+			CacheFileSink::Zstd(decoder) => decoder.write_all(buf),
+			CacheFileSink::Plain(writer) => writer.write_all(buf)
+		}
+	}
+
+	// Feeds in what we already have from a previous attempt
+	pub fn write_file(&mut self, file_path: &Path) -> io::Result<()> {
+		let mut file = File::open(file_path)?;
+
+		match self {
+			CacheFileSink::Zstd(decoder) => io::copy(&mut file, decoder),
+			CacheFileSink::Plain(writer) => io::copy(&mut file, writer)
+		}?;
+
+		Ok(())
+	}
+
+	// Returns the blake3 hash of the (decompressed) file
+	pub fn finish(self) -> io::Result<String> {
+		let mut writer = match self {
+			CacheFileSink::Zstd(mut decoder) => {
+				decoder.flush()?;
+				decoder.into_inner()
+			},
+			CacheFileSink::Plain(writer) => writer
+		};
+
+		writer.flush()?;
+
+		if let Some(file) = writer.file.take() {
+			file.into_inner().map_err(|error| error.into_error())?.sync_all()?;
+		}
+
+		Ok(format!("{}", writer.hasher.finalize()))
+	}
+}
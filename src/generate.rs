@@ -23,7 +23,11 @@ struct Args {
 	original_dest: PathBuf,
 
 	/// Path for where to copy the compressed versions of the Symbol files
-	symbol_dest: PathBuf
+	symbol_dest: PathBuf,
+
+	/// Path to a file with the hex-encoded ed25519 signing key, used to sign manifest.json, builds.json, version.txt, and launch_option_rules.json
+	#[arg(long)]
+	signing_key: Option<PathBuf>
 }
 
 fn get_files_recursive(source: &str, path_base: String, files: &mut HashMap<String, HashMap<String, PathBuf>>, dir_path: PathBuf) {
@@ -33,7 +37,10 @@ fn get_files_recursive(source: &str, path_base: String, files: &mut HashMap<Stri
 		let entry_filename = entry.file_name().into_string().unwrap();
 
 		// TODO: Move gmod-update.txt from these directories
-		if entry_filename != "gmod-update.txt" {
+		// <platform>/<branch>/steamapps is SteamCMD's, not GMod's (see read_manifest_build)
+		let steamcmd_dir = entry_filename == "steamapps" && path_base.matches("/").count() == 1;
+
+		if entry_filename != "gmod-update.txt" && !steamcmd_dir {
 			let source = if entry_filename.contains(".sym") { "symbol" } else { source };
 			let entry_filename = if entry_filename.contains(".sym") { entry_filename.replace(".sym", "") } else { entry_filename };
 			let entry_relative_path_str = if path_base.is_empty() { entry_filename } else { format!("{path_base}/{entry_filename}") };
@@ -58,6 +65,50 @@ fn get_files_recursive(source: &str, path_base: String, files: &mut HashMap<Stri
 	}
 }
 
+// The GMod build the Original files for a platform/branch are from, from the appmanifest_4000.acf SteamCMD leaves next to them
+fn read_manifest_build(branch_path: &Path) -> Result<Option<ManifestBuild>, String> {
+	let app_manifest_path = extend_pathbuf_and_return(branch_path.to_path_buf(), &["steamapps", "appmanifest_4000.acf"]);
+	if !app_manifest_path.is_file() {
+		return Ok(None);
+	}
+
+	let app_manifest = std::fs::read_to_string(&app_manifest_path).map_err(|error| error.to_string())?;
+	let app_manifest = vdf::Document::parse(&app_manifest).map_err(|error| error.to_string())?;
+
+	let build_id = app_manifest.get(&["AppState", "buildid"]).and_then(|build_id| build_id.parse().ok());
+	if build_id.is_none() {
+		return Err("appmanifest_4000.acf has no buildid".to_string());
+	}
+
+	let mut depots = IndexMap::new();
+	for depot_id in app_manifest.keys(&["AppState", "InstalledDepots"]).unwrap_or_default() {
+		if let Some(depot_manifest) = app_manifest.get(&["AppState", "InstalledDepots", depot_id, "manifest"]) {
+			depots.insert(depot_id.to_string(), depot_manifest.to_string());
+		}
+	}
+
+	depots.sort_unstable_keys();
+
+	Ok(Some(ManifestBuild {
+		build_id: build_id.unwrap(),
+		depots
+	}))
+}
+
+// Replace the stupid double-space indentation with proper tabbed indentation
+// Also add newline at the end to make Git happy
+fn to_json_string<T: Serialize>(value: &T) -> String {
+	let mut buf = Vec::new();
+	let formatter = serde_json::ser::PrettyFormatter::with_indent(b"	");
+	let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+	value.serialize(&mut ser).unwrap();
+	let json = unsafe {
+		String::from_utf8_unchecked(buf)
+	};
+
+	json + "\n"
+}
+
 fn hash_diff_compress_file(patch_dest: PathBuf, filename: &String, file_paths: &HashMap<String, PathBuf>, original_dest: PathBuf, symbol_dest: PathBuf) -> Result<(f64, IndexMap<String, String>), (bool, String)> {
 	let now = Instant::now();
 	let mut hashes: IndexMap<String, String> = IndexMap::new();
@@ -168,6 +219,7 @@ fn hash_diff_compress_file(patch_dest: PathBuf, filename: &String, file_paths: &
 		// Hash patch file (AFTER compression, since qbsdiff does it itself)
 		let patch_hash = format!("{}", blake3::hash(&patch));
 		hashes.insert("patch".to_string(), patch_hash);
+		hashes.insert("patch_size".to_string(), patch.len().to_string());
 	}
 
 	// Create a compressed copy of the original file
@@ -191,7 +243,7 @@ fn hash_diff_compress_file(patch_dest: PathBuf, filename: &String, file_paths: &
 			return Err((true, create_dir_result.to_string()));
 		}
 
-		let original_file_compressed = std::fs::OpenOptions::new().write(true).create(true).truncate(true).open(original_compressed_file_path);
+		let original_file_compressed = std::fs::OpenOptions::new().write(true).create(true).truncate(true).open(&original_compressed_file_path);
 		if let Err(original_file_compressed) = original_file_compressed {
 			return Err((true, original_file_compressed.to_string()));
 		}
@@ -201,6 +253,13 @@ fn hash_diff_compress_file(patch_dest: PathBuf, filename: &String, file_paths: &
 		if let Err(compress_result) = compress_result {
 			return Err((true, compress_result.to_string()));
 		}
+
+		// Download size, so the tool can tell how much it needs before downloading
+		let original_compressed_metadata = std::fs::metadata(&original_compressed_file_path);
+		if let Err(original_compressed_metadata) = original_compressed_metadata {
+			return Err((true, original_compressed_metadata.to_string()));
+		}
+		hashes.insert("original_size".to_string(), original_compressed_metadata.unwrap().len().to_string());
 	}
 
 	// Create compressed copies of fixed symbols
@@ -332,11 +391,35 @@ pub fn main() {
 		.exit();
 	}
 
+	let signing_key = args.signing_key.as_ref().map(|signing_key_path| {
+		let signing_key = std::fs::read_to_string(signing_key_path)
+			.map_err(|error| error.to_string())
+			.and_then(|signing_key_hex| signing::read_signing_key(&signing_key_hex));
+
+		match signing_key {
+			Ok(signing_key) => signing_key,
+			Err(error) => {
+				cmd.error(
+					ErrorKind::InvalidValue,
+					format!("Signing Key: {error}"),
+				)
+				.exit();
+			}
+		}
+	});
+
+	if let Some(signing_key) = &signing_key {
+		let public_key = signing::get_public_key_hex(signing_key);
+		println!("Signing Public Key: {public_key}\n");
+	}
+
 	let mut manifest_file_path = patch_dest.clone();
 	manifest_file_path.pop();
+	let manifest_dir_path = manifest_file_path.clone();
 	let manifest_file_path = extend_pathbuf_and_return(manifest_file_path, &["manifest.json"]);
+	let builds_file_path = extend_pathbuf_and_return(manifest_dir_path.clone(), &["builds.json"]);
 
-	println!("Deleting Old Patches Dir, Compressed Original Dir, and Manifest...");
+	println!("Deleting Old Patches Dir, Compressed Original Dir, Manifest, and Builds...");
 
 	let remove_result = std::fs::remove_dir_all(&patch_dest);
 	if let Err(remove_result) = remove_result {
@@ -373,10 +456,15 @@ pub fn main() {
 		println!("Failed to remove old manifest: {remove_result}");
 	}
 
+	let remove_result = std::fs::remove_file(&builds_file_path);
+	if let Err(remove_result) = remove_result {
+		println!("Failed to remove old builds: {remove_result}");
+	}
+
 	println!("\n*** GENERATING PATCH FILES ***\n");
 
 	let mut files: HashMap<String, HashMap<String, PathBuf>> = HashMap::new();
-	get_files_recursive("original", "".to_string(), &mut files, original_src);
+	get_files_recursive("original", "".to_string(), &mut files, original_src.clone());
 	get_files_recursive("fixed", "".to_string(), &mut files, fixed_src);
 
 	let manifest: Mutex<Manifest> = Mutex::new(IndexMap::new());
@@ -441,21 +529,82 @@ pub fn main() {
 
 	println!("\n*** GENERATING MANIFEST JSON ***\n");
 
-	// Replace the stupid double-space indentation with proper tabbed indentation
-	// Also add newline at the end to make Git happy
-	let mut buf = Vec::new();
-	let formatter = serde_json::ser::PrettyFormatter::with_indent(b"	");
-	let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
-	manifest.serialize(&mut ser).unwrap();
-	let mut manifest_json = unsafe {
-		String::from_utf8_unchecked(buf)
-	};
-
-	manifest_json += "\n";
+	let manifest_json = to_json_string(manifest);
 
 	let write_result = std::fs::write(manifest_file_path.clone(), &manifest_json);
 	write_result.unwrap();
 
+	println!("\n*** GENERATING BUILDS JSON ***\n");
+
+	// Same platform/branch layout as the manifest, so the tool can tell if GMod has updated since the patches were made
+	let mut builds: ManifestBuilds = IndexMap::new();
+	for platform in manifest.keys() {
+		for gmod_branch in manifest[platform].keys() {
+			let branch_path = extend_pathbuf_and_return(original_src.clone(), &[platform, gmod_branch]);
+
+			match read_manifest_build(&branch_path) {
+				Ok(Some(build)) => {
+					println!("\t{platform}/{gmod_branch}: Build {}", build.build_id);
+
+					builds.entry(platform.clone()).or_default().insert(gmod_branch.clone(), build);
+				},
+				Ok(None) => {
+					println!("\t{platform}/{gmod_branch}: No steamapps/appmanifest_4000.acf, so the tool can't check GMod's build for it");
+				},
+				Err(error) => {
+					println!("\t{platform}/{gmod_branch}: {error}\n\t\tFATAL ERROR, EXITING...\n");
+					std::process::exit(1);
+				}
+			}
+		}
+	}
+
+	let builds_json = to_json_string(&builds);
+
+	let write_result = std::fs::write(builds_file_path.clone(), &builds_json);
+	write_result.unwrap();
+
+	// Sign the manifest and builds, and version.txt + launch_option_rules.json (which are edited by hand) while we're at it
+	let signed_filenames = ["manifest.json", "builds.json", "version.txt", "launch_option_rules.json"];
+	if let Some(signing_key) = &signing_key {
+		println!("\n*** SIGNING MANIFEST, BUILDS, VERSION, AND LAUNCH OPTION RULES ***\n");
+
+		// The tool won't take a file signed before one it's already used, so old signed files can't be served again
+		let signed_at = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+
+		for filename in signed_filenames {
+			let file_path = extend_pathbuf_and_return(manifest_dir_path.clone(), &[filename]);
+			let signature_path = extend_pathbuf_and_return(manifest_dir_path.clone(), &[format!("{filename}{}", signing::SIGNATURE_EXTENSION).as_str()]);
+
+			match std::fs::read(&file_path) {
+				Ok(contents) => {
+					std::fs::write(signature_path, signing::sign(signing_key, &contents, signed_at)).unwrap();
+					println!("Signed: {filename}");
+				},
+				Err(error) => {
+					println!("Couldn't sign {filename}: {error}");
+				}
+			}
+		}
+	} else {
+		// Signatures left over from the last run don't match the files anymore
+		println!("\n*** NO --signing-key, REMOVING OLD SIGNATURES ***\n");
+
+		for filename in signed_filenames {
+			let signature_path = extend_pathbuf_and_return(manifest_dir_path.clone(), &[format!("{filename}{}", signing::SIGNATURE_EXTENSION).as_str()]);
+
+			match std::fs::remove_file(&signature_path) {
+				Ok(_) => println!("Removed: {filename}{}", signing::SIGNATURE_EXTENSION),
+				Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+				Err(error) => {
+					println!("Failed to remove old signature for {filename}: {error}");
+				}
+			}
+		}
+
+		println!("The tool refuses unsigned files, so sign these before publishing them.");
+	}
+
 	let now = now.elapsed().as_secs_f64();
 	println!("Patch generation complete! Took {now} second(s).");
 }
@@ -0,0 +1,188 @@
+use crate::*;
+use crate::patch::IntegrityStatus;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+const JOURNAL_FILENAME: &str = "journal.jsonl";
+const TEMP_FILE_SUFFIX: &str = ".gmodpatchtool-tmp";
+
+// One line of the journal
+// The journal only exists while a patch run is in progress, so if it's there on launch, the last run was interrupted (or failed, if it ends with Failed)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEntry {
+	Begin {
+		gmod_path: String,
+		platform: String,
+		branch: String
+	},
+	Backup {
+		filename: String,
+		hash: String
+	},
+	// The file was there, but it wasn't the original, so there's no backup to roll it back to
+	NotBackedUp {
+		filename: String
+	},
+	Start {
+		filename: String,
+		status: IntegrityStatus
+	},
+	Transition {
+		filename: String,
+		from: IntegrityStatus,
+		to: IntegrityStatus
+	},
+	// The run stopped on an error, instead of crashing or being killed
+	Failed {
+		error: String
+	}
+}
+
+pub struct Journal {
+	path: PathBuf,
+	file: Mutex<File>
+}
+
+impl Journal {
+	pub fn begin(backup_dir: &Path, gmod_path: &Path, platform: &str, branch: &str) -> Result<Journal, String> {
+		std::fs::create_dir_all(backup_dir).map_err(|error| error.to_string())?;
+
+		let path = extend_pathbuf_and_return(backup_dir.to_path_buf(), &[JOURNAL_FILENAME]);
+		let file = File::create(&path).map_err(|error| error.to_string())?;
+		let journal = Journal {
+			path,
+			file: Mutex::new(file)
+		};
+
+		journal.record(&JournalEntry::Begin {
+			gmod_path: gmod_path.to_string_lossy().to_string(),
+			platform: platform.to_string(),
+			branch: branch.to_string()
+		})?;
+
+		Ok(journal)
+	}
+
+	// Entries are synced to disk before returning, so they survive a power loss
+	pub fn record(&self, entry: &JournalEntry) -> Result<(), String> {
+		let mut line = serde_json::to_string(entry).map_err(|error| error.to_string())?;
+		line += "\n";
+
+		let mut file = self.file.lock().unwrap();
+		file.write_all(line.as_bytes()).map_err(|error| error.to_string())?;
+		file.sync_data().map_err(|error| error.to_string())
+	}
+
+	pub fn record_start(&self, filename: &str, status: IntegrityStatus) -> Result<(), String> {
+		self.record(&JournalEntry::Start {
+			filename: filename.to_string(),
+			status
+		})
+	}
+
+	pub fn record_transition(&self, filename: &str, from: IntegrityStatus, to: IntegrityStatus) -> Result<(), String> {
+		self.record(&JournalEntry::Transition {
+			filename: filename.to_string(),
+			from,
+			to
+		})
+	}
+
+	// The run stopped on an error; what it did can still be resumed or rolled back, so the journal stays
+	pub fn fail(self, error: &str) -> Result<(), String> {
+		self.record(&JournalEntry::Failed {
+			error: error.to_string()
+		})
+	}
+
+	// The run completed, so there's nothing to resume or roll back
+	pub fn finish(self) -> Result<(), String> {
+		drop(self.file);
+		std::fs::remove_file(self.path).map_err(|error| error.to_string())
+	}
+}
+
+// Returns the entries of an interrupted run's journal, if there is one
+pub fn read_interrupted_journal(backup_dir: &Path) -> Option<Vec<JournalEntry>> {
+	let path = extend_pathbuf_and_return(backup_dir.to_path_buf(), &[JOURNAL_FILENAME]);
+	let journal_str = std::fs::read_to_string(path).ok()?;
+
+	// A torn last line means we were killed mid-write; everything before it is still valid
+	let entries = journal_str.lines()
+		.filter_map(|line| serde_json::from_str(line).ok())
+		.collect();
+
+	Some(entries)
+}
+
+pub fn clear_journal(backup_dir: &Path) -> Result<(), String> {
+	let path = extend_pathbuf_and_return(backup_dir.to_path_buf(), &[JOURNAL_FILENAME]);
+
+	match std::fs::remove_file(path) {
+		Ok(_) => Ok(()),
+		Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+		Err(error) => Err(error.to_string())
+	}
+}
+
+pub fn get_temp_file_path(file_path: &Path) -> PathBuf {
+	let mut temp_file_name = file_path.file_name().unwrap_or_default().to_os_string();
+	temp_file_name.push(TEMP_FILE_SUFFIX);
+
+	file_path.with_file_name(temp_file_name)
+}
+
+// Renames are only durable once the directory entry itself is synced
+// Not supported on Windows, where the rename is already durable
+fn sync_parent_dir(file_path: &Path) {
+	#[cfg(unix)]
+	if let Some(file_path_dir) = file_path.parent() && let Ok(dir) = File::open(file_path_dir) {
+		let _ = dir.sync_all();
+	}
+
+	#[cfg(not(unix))]
+	let _ = file_path;
+}
+
+// Moves a fully written temp file over the target, keeping the target's permissions
+fn commit_temp_file(temp_file_path: &Path, file_path: &Path) -> io::Result<()> {
+	{
+		let temp_file = std::fs::OpenOptions::new().write(true).open(temp_file_path)?;
+		temp_file.sync_all()?;
+	}
+
+	if let Ok(metadata) = std::fs::metadata(file_path) {
+		std::fs::set_permissions(temp_file_path, metadata.permissions())?;
+	}
+
+	std::fs::rename(temp_file_path, file_path)?;
+	sync_parent_dir(file_path);
+
+	Ok(())
+}
+
+// Either the old or new contents will be at file_path if we're interrupted, never a partial file
+pub fn write_file_atomic(file_path: &Path, contents: &[u8]) -> io::Result<()> {
+	let temp_file_path = get_temp_file_path(file_path);
+
+	let result = std::fs::write(&temp_file_path, contents).and_then(|_| commit_temp_file(&temp_file_path, file_path));
+	if result.is_err() {
+		let _ = std::fs::remove_file(&temp_file_path);
+	}
+
+	result
+}
+
+pub fn copy_file_atomic(source_path: &Path, file_path: &Path) -> io::Result<()> {
+	let temp_file_path = get_temp_file_path(file_path);
+
+	let result = std::fs::copy(source_path, &temp_file_path).and_then(|_| commit_temp_file(&temp_file_path, file_path));
+	if result.is_err() {
+		let _ = std::fs::remove_file(&temp_file_path);
+	}
+
+	result
+}
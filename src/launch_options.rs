@@ -0,0 +1,372 @@
+// GMod's Steam Launch Options: linting them, and editing them in localconfig.vdf
+use serde::{Deserialize, Serialize};
+
+use super::vdf;
+
+// GMod's launcher script (hl2.sh) drops LD_PRELOAD unless this is set, to exactly 1
+// launch_option_rules.json's ld_preload_without_gmod_enable_ld_preload rule requires the same option
+pub const GMOD_ENABLE_LD_PRELOAD: &str = "GMOD_ENABLE_LD_PRELOAD=1";
+
+// Launch Options split up, so single options can be added/removed
+// e.g. `GMOD_ENABLE_LD_PRELOAD=1 %command% -chromium_fps_max 60` is prefix ["GMOD_ENABLE_LD_PRELOAD=1"] and args [["-chromium_fps_max", "60"]]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+	// Environment variables and wrapper commands before %command%
+	pub prefix: Vec<String>,
+	// Arguments after %command%, each with its values
+	pub args: Vec<Vec<String>>,
+	// Whether %command% was there, even with nothing before it
+	has_command: bool
+}
+
+impl LaunchOptions {
+	// Without %command%, Steam passes everything to GMod as arguments
+	pub fn parse(launch_options: &str) -> LaunchOptions {
+		let tokens = split_launch_options(launch_options);
+
+		match tokens.iter().position(|token| token == "%command%") {
+			Some(command_pos) => LaunchOptions {
+				prefix: tokens[..command_pos].to_vec(),
+				args: group_args(&tokens[command_pos + 1..]),
+				has_command: true
+			},
+			None => LaunchOptions {
+				prefix: vec![],
+				args: group_args(&tokens),
+				has_command: false
+			}
+		}
+	}
+
+	// Options from the command line, where %command% is optional
+	// Leading VAR=value options are environment variables; everything else is arguments
+	pub fn parse_input(input: &str) -> LaunchOptions {
+		let tokens: Vec<String> = split_launch_options(input).into_iter().filter(|token| token != "%command%").collect();
+		let prefix_len = tokens.iter().take_while(|token| is_env_var(token)).count();
+
+		LaunchOptions {
+			prefix: tokens[..prefix_len].to_vec(),
+			args: group_args(&tokens[prefix_len..]),
+			has_command: false
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.prefix.is_empty() && self.args.is_empty()
+	}
+
+	// Adds options, replacing any that are already there (so values get updated)
+	pub fn add(&mut self, other: &LaunchOptions) {
+		for prefix_option in &other.prefix {
+			replace_option(&mut self.prefix, prefix_option, |option| get_option_key(option));
+
+			// Environment variables that were in the arguments by mistake (without %command%) move to where they work
+			let prefix_option_key = get_option_key(prefix_option);
+			self.args.retain(|arg| get_option_key(&arg[0]) != prefix_option_key);
+		}
+
+		// Anything before the arguments needs %command% after it
+		self.has_command |= !self.prefix.is_empty();
+
+		for arg in &other.args {
+			replace_option(&mut self.args, arg, |option| get_option_key(&option[0]));
+		}
+	}
+
+	// Removes an option by name (e.g. -nochromium or GMOD_ENABLE_LD_PRELOAD), with its value
+	// Returns whether it was there
+	pub fn remove(&mut self, option: &str) -> bool {
+		let option_key = get_option_key(option);
+		// Environment variables can be removed by just their name
+		let env_var_name = option.split_once('=').map_or(option, |(name, _)| name);
+		let option_count = self.prefix.len() + self.args.len();
+
+		self.prefix.retain(|existing| get_option_key(existing) != option_key && get_option_key(existing) != env_var_name);
+		self.args.retain(|existing| get_option_key(&existing[0]) != option_key);
+
+		self.prefix.len() + self.args.len() != option_count
+	}
+
+	// Each option on its own, for listing them
+	pub fn items(&self) -> Vec<String> {
+		self.prefix.iter().cloned().chain(self.args.iter().map(|arg| arg.join(" "))).collect()
+	}
+
+	// Whether LD_PRELOAD makes it past GMod's launcher script
+	pub fn enables_ld_preload(&self) -> bool {
+		!self.find(GMOD_ENABLE_LD_PRELOAD).is_empty()
+	}
+
+	// Options matching a rule's option, as they're written
+	// Environment variables only count before %command%; anywhere else, Steam passes them to GMod as arguments
+	fn find(&self, rule_option: &str) -> Vec<String> {
+		if is_env_var_rule_option(rule_option) {
+			if !self.has_command {
+				return vec![];
+			}
+
+			return self.prefix.iter().filter(|option| matches_rule_option(option, rule_option)).cloned().collect();
+		}
+
+		let prefix_matches = self.prefix.iter().filter(|option| matches_rule_option(option, rule_option)).cloned();
+		let arg_matches = self.args.iter().filter(|arg| matches_rule_option(&arg[0], rule_option)).map(|arg| arg.join(" "));
+
+		prefix_matches.chain(arg_matches).collect()
+	}
+
+	// Checks the options against every rule that applies to how GMod is run
+	pub fn lint(&self, rules: &[LaunchOptionRule], platform: &str, compat_tool: &str) -> Vec<LintFinding> {
+		let mut findings = vec![];
+
+		for rule in rules {
+			if rule.platform.as_ref().is_some_and(|rule_platform| rule_platform != platform) || rule.compat_tool.as_ref().is_some_and(|rule_compat_tool| !compat_tool.contains(rule_compat_tool.as_str())) {
+				continue;
+			}
+
+			let matched_options: Vec<String> = match &rule.check {
+				RuleCheck::Present { options } => options.iter().flat_map(|option| self.find(option)).collect(),
+				RuleCheck::Conflict { options } => {
+					let present_options: Vec<String> = options.iter().flat_map(|option| self.find(option)).collect();
+
+					if present_options.len() > 1 {
+						vec![present_options.join(" and ")]
+					} else {
+						vec![]
+					}
+				},
+				RuleCheck::Duplicate => {
+					let mut seen_keys: Vec<String> = vec![];
+					let mut duplicate_keys: Vec<String> = vec![];
+					let mut duplicates: Vec<String> = vec![];
+
+					let keys = self.prefix.iter().map(|option| get_option_key(option)).chain(self.args.iter().map(|arg| get_option_key(&arg[0])));
+					for (key, item) in keys.zip(self.items()) {
+						if !seen_keys.contains(&key) {
+							seen_keys.push(key);
+						} else if !duplicate_keys.contains(&key) {
+							duplicate_keys.push(key);
+							duplicates.push(item);
+						}
+					}
+
+					duplicates
+				},
+				RuleCheck::Requires { option, requires } => {
+					if self.find(requires).is_empty() {
+						self.find(option)
+					} else {
+						vec![]
+					}
+				},
+				RuleCheck::EnvVarWithoutCommand => self.args.iter().flatten().filter(|token| is_env_var(token)).cloned().collect()
+			};
+
+			for matched_option in matched_options {
+				findings.push(LintFinding {
+					id: rule.id.clone(),
+					severity: rule.severity,
+					message: rule.message.replace("{option}", &matched_option),
+					fix: rule.fix.replace("{option}", &matched_option)
+				});
+			}
+		}
+
+		findings
+	}
+}
+
+impl std::fmt::Display for LaunchOptions {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut tokens: Vec<&str> = self.prefix.iter().map(String::as_str).collect();
+
+		if self.has_command || !self.prefix.is_empty() {
+			tokens.push("%command%");
+		}
+
+		tokens.extend(self.args.iter().flatten().map(String::as_str));
+
+		write!(f, "{}", tokens.join(" "))
+	}
+}
+
+// Splits on whitespace, except inside quotes (e.g. LD_PRELOAD="a.so b.so")
+fn split_launch_options(launch_options: &str) -> Vec<String> {
+	let mut tokens = vec![];
+	let mut token = String::new();
+	let mut in_quotes = false;
+
+	for char in launch_options.chars() {
+		if char == '"' {
+			in_quotes = !in_quotes;
+		}
+
+		if char.is_whitespace() && !in_quotes {
+			if !token.is_empty() {
+				tokens.push(std::mem::take(&mut token));
+			}
+		} else {
+			token.push(char);
+		}
+	}
+
+	if !token.is_empty() {
+		tokens.push(token);
+	}
+
+	tokens
+}
+
+// Each -arg/+arg starts a new group, and takes everything after it until the next one as its values
+fn group_args(tokens: &[String]) -> Vec<Vec<String>> {
+	let mut args: Vec<Vec<String>> = vec![];
+
+	for token in tokens {
+		match args.last_mut() {
+			Some(arg) if !token.starts_with(['-', '+']) && arg[0].starts_with(['-', '+']) => arg.push(token.clone()),
+			_ => args.push(vec![token.clone()])
+		}
+	}
+
+	args
+}
+
+// Replaces the first option with the same key (dropping any other copies), or adds it to the end
+fn replace_option<T: Clone>(options: &mut Vec<T>, option: &T, get_key: impl Fn(&T) -> String) {
+	let key = get_key(option);
+
+	match options.iter().position(|existing| get_key(existing) == key) {
+		Some(existing_pos) => {
+			options[existing_pos] = option.clone();
+
+			let mut option_id = 0;
+			options.retain(|existing| {
+				let keep = option_id <= existing_pos || get_key(existing) != key;
+				option_id += 1;
+				keep
+			});
+		},
+		None => options.push(option.clone())
+	}
+}
+
+fn is_env_var(token: &str) -> bool {
+	token.split_once('=').is_some_and(|(name, _)| !name.is_empty() && name.chars().all(|char| char.is_ascii_alphanumeric() || char == '_'))
+}
+
+// What two options need in common to be the same option: VAR for VAR=value, otherwise the name itself
+// GMod's arguments aren't case sensitive
+fn get_option_key(option: &str) -> String {
+	if is_env_var(option) {
+		option.split_once('=').unwrap().0.to_string()
+	} else {
+		option.to_lowercase()
+	}
+}
+
+// VAR=value or VAR, as opposed to an argument (-arg/+arg)
+fn is_env_var_rule_option(rule_option: &str) -> bool {
+	is_env_var(rule_option) || (!rule_option.is_empty() && rule_option.chars().all(|char| char.is_ascii_alphanumeric() || char == '_'))
+}
+
+// Rule options are VAR=value (exactly that), VAR (any value), or an argument (any case)
+fn matches_rule_option(option: &str, rule_option: &str) -> bool {
+	if rule_option.contains('=') {
+		option == rule_option
+	} else if is_env_var(option) {
+		get_option_key(option) == rule_option
+	} else {
+		option.eq_ignore_ascii_case(rule_option)
+	}
+}
+
+//
+// Linting
+// The rules are in launch_option_rules.json; mirrors can have a newer copy, so bad advice from new guides is caught without a release
+//
+pub const BUILTIN_RULES: &str = include_str!("../launch_option_rules.json");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+	Info,
+	Warning,
+	Error
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum RuleCheck {
+	// Any of these options is there
+	Present { options: Vec<String> },
+	// More than one of these options is there
+	Conflict { options: Vec<String> },
+	// Any option is there more than once
+	Duplicate,
+	// option is there without requires
+	Requires { option: String, requires: String },
+	// An environment variable (VAR=value) is in the arguments, where it does nothing
+	EnvVarWithoutCommand
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LaunchOptionRule {
+	pub id: String,
+	pub severity: Severity,
+	#[serde(flatten)]
+	pub check: RuleCheck,
+	// Only applies to this target platform (e.g. linux for native GMod on Linux)
+	#[serde(default)]
+	pub platform: Option<String>,
+	// Only applies if GMod's compat tool name contains this (e.g. proton)
+	#[serde(default)]
+	pub compat_tool: Option<String>,
+	// {option} is replaced with the option(s) that matched
+	pub message: String,
+	pub fix: String
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintFinding {
+	pub id: String,
+	pub severity: Severity,
+	pub message: String,
+	pub fix: String
+}
+
+#[derive(Deserialize)]
+struct LaunchOptionRulesFile {
+	rules: Vec<serde_json::Value>
+}
+
+// Rules this version doesn't understand (e.g. a newer check) are skipped, so older versions keep working with newer rules
+pub fn parse_rules(rules_json: &[u8]) -> Result<Vec<LaunchOptionRule>, String> {
+	let rules_file: LaunchOptionRulesFile = serde_json::from_slice(rules_json).map_err(|error| error.to_string())?;
+
+	Ok(rules_file.rules.into_iter().filter_map(|rule| serde_json::from_value(rule).ok()).collect())
+}
+
+// Newer rules replace built-in ones with the same id
+pub fn merge_rules(builtin_rules: Vec<LaunchOptionRule>, newer_rules: Vec<LaunchOptionRule>) -> Vec<LaunchOptionRule> {
+	let mut rules: Vec<LaunchOptionRule> = builtin_rules.into_iter().filter(|builtin_rule| !newer_rules.iter().any(|newer_rule| newer_rule.id == builtin_rule.id)).collect();
+	rules.extend(newer_rules);
+
+	rules
+}
+
+// Sets an app's LaunchOptions in localconfig.vdf's contents, adding the key if it isn't there
+// Only the LaunchOptions value is touched, so everything else is written back exactly as Steam left it
+pub fn set_localconfig_launch_options(localconfig_str: &str, app_id: u32, launch_options: &str) -> Result<String, String> {
+	let app_id_str = app_id.to_string();
+	let app_path = ["UserLocalConfigStore", "Software", "Valve", "Steam", "apps", app_id_str.as_str()];
+
+	let mut localconfig = vdf::Document::parse(localconfig_str).map_err(|error| error.to_string())?;
+
+	// Steam only adds the app once it knows about it
+	if !localconfig.has_section(&app_path) {
+		return Err(format!("Couldn't find app {app_id} in localconfig.vdf"));
+	}
+
+	localconfig.set(&[app_path.as_slice(), &["LaunchOptions"]].concat(), launch_options).map_err(|error| error.to_string())?;
+
+	Ok(localconfig.to_string())
+}
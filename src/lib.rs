@@ -4,11 +4,40 @@ pub mod generate;
 #[cfg(feature = "patch")]
 pub mod patch;
 
+#[cfg(feature = "patch")]
+mod backup;
+
+#[cfg(feature = "patch")]
+mod bundle;
+
+#[cfg(feature = "patch")]
+mod config;
+
+#[cfg(feature = "patch")]
+mod download;
+
 #[cfg(feature = "patch")]
 mod gui;
 
 #[cfg(feature = "patch")]
-mod vdf;
+mod journal;
+
+#[cfg(feature = "patch")]
+pub mod launch_options;
+
+#[cfg(feature = "patch")]
+mod mirrors;
+
+#[cfg(feature = "patch")]
+mod progress;
+
+mod signing;
+
+#[cfg(feature = "patch")]
+pub mod steam;
+
+#[cfg(any(feature = "patch", feature = "generate"))]
+pub mod vdf;
 
 const ABOUT: &str = r#"   ________  ___          ______        __       __  ______            __
   / ____/  |/  /___  ____/ / __ \____ _/ /______/ /_/_  __/___  ____  / /
@@ -32,9 +61,21 @@ use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use indexmap::IndexMap;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 type Manifest = IndexMap<String, IndexMap<String, IndexMap<String, IndexMap<String, String>>>>;
 
+// builds.json, next to manifest.json: the GMod build (and depot manifests) each platform/branch's patches were made for
+// Separate from manifest.json, since older versions of the tool would take anything new in there for files to patch
+#[derive(Serialize, Deserialize, Debug)]
+struct ManifestBuild {
+	build_id: u32,
+	// Depot ID -> depot manifest ID
+	depots: IndexMap<String, String>
+}
+
+type ManifestBuilds = IndexMap<String, IndexMap<String, ManifestBuild>>;
+
 fn pathbuf_dir_not_empty(pathbuf: &Path) -> bool {
 	// If this is a valid file in the directory, the directory isn't empty
 	if pathbuf.is_file() {
@@ -0,0 +1,107 @@
+use crate::*;
+
+// Version and Manifest files
+const TEXT_SERVER_ROOTS: [&str; 2] = [
+	"https://raw.githubusercontent.com/solsticegamestudios/GModPatchTool/refs/heads/files/",
+	"https://www.solsticegamestudios.com/gmodpatchtool/"
+];
+
+// Patch files
+const BINARY_SERVER_ROOTS: [&str; 2] = [
+	"https://media.githubusercontent.com/media/solsticegamestudios/GModPatchTool/refs/heads/files/",
+	"https://www.solsticegamestudios.com/gmodpatchtool/" // TODO: Webhook that triggers git pull and clears the cache on Cloudflare
+];
+
+// A copy of the files branch, either on a server or on disk (file://, --offline-bundle)
+#[derive(Clone, Debug)]
+pub enum MirrorRoot {
+	Http(String),
+	Local(PathBuf)
+}
+
+impl MirrorRoot {
+	pub fn parse(root: &str) -> Result<MirrorRoot, String> {
+		// Plain paths are allowed too, as long as they exist
+		if Path::new(root).is_dir() {
+			return Ok(MirrorRoot::Local(PathBuf::from(root)));
+		}
+
+		let url = reqwest::Url::parse(root).map_err(|error| format!("Invalid mirror ({root}): {error}"))?;
+		match url.scheme() {
+			"http" | "https" => {
+				// Filenames get appended directly, so make sure we don't drop the last path segment
+				let mut root = url.to_string();
+				if !root.ends_with("/") {
+					root += "/";
+				}
+
+				Ok(MirrorRoot::Http(root))
+			},
+			"file" => url.to_file_path()
+				.map(MirrorRoot::Local)
+				.map_err(|_| format!("Invalid mirror ({root}): Not a valid local path")),
+			scheme => Err(format!("Invalid mirror ({root}): Unsupported scheme {scheme}"))
+		}
+	}
+
+	pub fn get_file_location(&self, filename: &str) -> String {
+		match self {
+			MirrorRoot::Http(root) => root.clone() + filename,
+			MirrorRoot::Local(root) => {
+				let file_parts: Vec<&str> = filename.split("/").collect();
+
+				extend_pathbuf_and_return(root.clone(), &file_parts[..]).to_string_lossy().to_string()
+			}
+		}
+	}
+}
+
+impl std::fmt::Display for MirrorRoot {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MirrorRoot::Http(root) => write!(f, "{root}"),
+			MirrorRoot::Local(root) => write!(f, "{}", root.display())
+		}
+	}
+}
+
+// Mirrors are tried in order until one of them has the file
+#[derive(Clone, Debug)]
+pub struct MirrorList {
+	pub text: Vec<MirrorRoot>,
+	pub binary: Vec<MirrorRoot>
+}
+
+impl MirrorList {
+	// Custom mirrors serve both the text and binary files, since they're a copy of the whole files branch
+	pub fn new(custom_roots: &[String], use_default_mirrors: bool) -> Result<MirrorList, String> {
+		let mut mirrors = MirrorList {
+			text: Vec::new(),
+			binary: Vec::new()
+		};
+
+		for root in custom_roots {
+			let root = MirrorRoot::parse(root)?;
+			mirrors.text.push(root.clone());
+			mirrors.binary.push(root);
+		}
+
+		if use_default_mirrors {
+			mirrors.text.extend(TEXT_SERVER_ROOTS.iter().map(|root| MirrorRoot::Http(root.to_string())));
+			mirrors.binary.extend(BINARY_SERVER_ROOTS.iter().map(|root| MirrorRoot::Http(root.to_string())));
+		}
+
+		if mirrors.text.is_empty() {
+			return Err("No mirrors configured! Add one with --mirror, or don't disable the default mirrors.".to_string());
+		}
+
+		Ok(mirrors)
+	}
+
+	pub fn local(root: PathBuf) -> MirrorList {
+		MirrorList {
+			text: vec![MirrorRoot::Local(root.clone())],
+			binary: vec![MirrorRoot::Local(root)]
+		}
+	}
+}
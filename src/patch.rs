@@ -1,39 +1,31 @@
-// Version and Manifest files
-const TEXT_SERVER_ROOTS: [&str; 2] = [
-	"https://raw.githubusercontent.com/solsticegamestudios/GModPatchTool/refs/heads/files/",
-	"https://www.solsticegamestudios.com/gmodpatchtool/"
-];
-
-// Patch files
-const BINARY_SERVER_ROOTS: [&str; 2] = [
-	"https://media.githubusercontent.com/media/solsticegamestudios/GModPatchTool/refs/heads/files/",
-	"https://www.solsticegamestudios.com/gmodpatchtool/" // TODO: Webhook that triggers git pull and clears the cache on Cloudflare
-];
-
 //const GMOD_STEAM_APPID: u64 = 4000;
 const BLANK_FILE_HASH: &str = "null";
 
 use crate::*;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::error;
 use tracing_subscriber::filter::EnvFilter;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use std::io::IsTerminal;
+use std::sync::OnceLock;
 use phf::phf_map;
 use phf::Map;
 use std::time;
 use steamid::SteamId;
 use sysinfo::System;
 use std::fs::File;
-use std::io;
-use reqwest::Response;
+use std::io::{self, Seek, Write};
+use std::sync::Arc;
 use tokio::time::Instant;
 use tokio::task::JoinSet;
 use qbsdiff::Bspatch;
-use regex::Regex;
 
 use super::vdf;
+use super::launch_options;
+use super::steam::{self, SteamInstallation};
+use super::mirrors::{MirrorList, MirrorRoot};
+use super::progress::{FileProgress, ProgressSnapshot, TotalProgress};
 
 #[cfg(windows)]
 use is_elevated::is_elevated;
@@ -47,6 +39,9 @@ use std::os::unix::fs::PermissionsExt;
 #[derive(Parser)]
 #[command(version)]
 struct Args {
+	#[command(subcommand)]
+	command: Option<Command>,
+
 	/// Launch Garry's Mod after successfully patching
 	#[arg(short, long)]
 	launch_gmod: bool,
@@ -55,9 +50,29 @@ struct Args {
 	#[arg(short, long)]
 	skip_exit_prompt: bool,
 
-	/// Force a specific Steam install path (NOT a Steam library path)
-	#[arg(long)]
-	steam_path: Option<PathBuf>,
+	/// Use a specific Steam install: its number from list-steam, or its path (NOT a Steam library path)
+	#[arg(long, value_name = "N_OR_PATH", alias = "steam-path")]
+	steam_install: Option<String>,
+
+	/// Patch this GarrysMod directory without looking for it through Steam (requires --branch and --platform)
+	#[arg(long, value_name = "PATH", requires_all = ["branch", "platform"])]
+	gmod_path: Option<PathBuf>,
+
+	/// Beta Branch of the --gmod-path install (e.g. public or x86-64)
+	#[arg(long, requires = "gmod_path")]
+	branch: Option<String>,
+
+	/// Platform of the --gmod-path install's files (windows for Proton)
+	#[arg(long, value_parser = ["windows", "linux", "macos"], requires = "gmod_path")]
+	platform: Option<String>,
+
+	/// Which Garry's Mod install to patch if there's more than one: active (the one Steam uses), all, its number, or its path
+	#[arg(long, value_name = "WHICH", default_value = "active")]
+	gmod_install: String,
+
+	/// Use a specific Steam User's settings: their SteamID64 or account name (see list-steam)
+	#[arg(long, value_name = "STEAMID64_OR_NAME")]
+	steam_user: Option<String>,
 
 	/// Don't apply SourceScheme (VGUI Theme) changes
 	#[arg(long)]
@@ -73,9 +88,117 @@ struct Args {
 
 	/// Allow running the tool as root/admin (NOT RECOMMENDED!!!)
 	#[arg(long)]
-	run_as_root_with_security_risk: bool
+	run_as_root_with_security_risk: bool,
+
+	/// If the last patch run was interrupted, undo the files it touched instead of resuming
+	#[arg(long)]
+	rollback_interrupted: bool,
+
+	/// Patch even if the patches were made for a different Garry's Mod build than the installed one (NOT RECOMMENDED, may roll back updated files)
+	#[arg(long)]
+	ignore_build_mismatch: bool,
+
+	/// Only print what would be patched and downloaded, without changing anything (exits with 2 if patching is needed)
+	#[arg(long)]
+	dry_run: bool,
+
+	/// Download from this mirror (http://, https://, or file://) before the built-in ones; can be given multiple times, in priority order
+	#[arg(long, value_name = "URL")]
+	mirror: Vec<String>,
+
+	/// Only use the mirrors from --mirror and the config file, never the built-in ones
+	#[arg(long)]
+	no_default_mirrors: bool,
+
+	/// Read settings from this config file instead of the default location
+	#[arg(long, value_name = "PATH")]
+	config: Option<PathBuf>,
+
+	/// Maximum number of files to download at once
+	#[arg(long, value_name = "N", default_value_t = download::DEFAULT_MAX_PARALLEL_DOWNLOADS, value_parser = clap::value_parser!(u16).range(1..))]
+	max_parallel_downloads: u16,
+
+	/// Limit the total download speed across all files, in bytes per second (e.g. 500K or 2M)
+	#[arg(long, value_name = "RATE", value_parser = download::parse_rate)]
+	limit_rate: Option<u64>,
+
+	/// Use a local copy of the patch files (directory, .tar, or .tar.zst) instead of downloading them
+	#[arg(long, value_name = "DIR_OR_ARCHIVE")]
+	offline_bundle: Option<PathBuf>,
+
+	/// Output format; json prints one event object per line for launchers/scripts
+	#[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+	output: OutputFormat
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OutputFormat {
+	#[default]
+	Text,
+	Json
+}
+
+// Set once the args are parsed, before anything else is written
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+fn output_format() -> OutputFormat {
+	OUTPUT_FORMAT.get().copied().unwrap_or_default()
+}
+
+#[derive(Subcommand, Clone, PartialEq, Eq)]
+enum Command {
+	/// Restore all patched files to their unpatched originals (uses backups where possible, otherwise downloads them)
+	#[command(alias = "unpatch")]
+	Restore,
+
+	/// Same as --dry-run
+	Check,
+
+	/// List the Steam installs we can find, with their users, libraries, and whether Garry's Mod is installed
+	ListSteam,
+
+	/// List, add, or remove Garry's Mod's Launch Options in Steam (Steam must be closed to change them)
+	LaunchOptions {
+		#[command(subcommand)]
+		action: LaunchOptionsAction
+	}
+}
+
+#[derive(Subcommand, Clone, PartialEq, Eq)]
+enum LaunchOptionsAction {
+	/// List Garry's Mod's Launch Options
+	List,
+
+	/// Add a Launch Option, or change its value if it's already there (e.g. -chromium_fps_max 60, or GMOD_ENABLE_LD_PRELOAD=1)
+	Add {
+		#[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true, value_name = "OPTION")]
+		option: Vec<String>
+	},
+
+	/// Remove a Launch Option and its value (e.g. -nochromium, or GMOD_ENABLE_LD_PRELOAD)
+	Remove {
+		#[arg(allow_hyphen_values = true, value_name = "OPTION")]
+		option: String
+	}
 }
 
+// What happened, for scripting via the exit code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RunOutcome {
+	Done,
+	// Everything is patched, so Garry's Mod is ready to launch
+	Patched,
+	// --dry-run found files that need patching/restoring
+	ChangesNeeded
+}
+
+const EXIT_CODE_ERROR: i32 = 1;
+const EXIT_CODE_CHANGES_NEEDED: i32 = 2;
+
+// How many major versions older than Proton GMod's prefix can be before we warn about it
+#[cfg(target_os = "linux")]
+const PROTON_PREFIX_MAX_VERSION_GAP: u32 = 1;
+
 const COLOR_LOOKUP: Map<&'static str, &'static str> =
 phf_map! {
 	"red" => "\x1B[1;31m",
@@ -92,160 +215,13 @@ enum AlmightyError {
 	Http(#[from] reqwest::Error),
 	#[error("Remote Version parsing error: {0}")]
 	Parse(#[from] std::num::ParseIntError),
+	#[error("Remote Manifest parsing error: {0}")]
+	Json(#[from] serde_json::Error),
 	#[error("{0}")]
 	Generic(String)
 }
 
 // VDF structs
-//
-// Steam/config/loginusers.vdf
-//
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamUser {
-	#[serde(alias = "accountname")]
-	account_name: String,
-	#[serde(alias = "personaname")]
-	persona_name: String,
-	//remember_password: bool,
-	//wants_offline_mode: bool,
-	//skip_offline_mode_warning: bool,
-	//allow_auto_login: bool,
-	#[serde(alias = "mostrecent")]
-	most_recent: bool,
-	#[serde(alias = "timestamp")]
-	timestamp: u64 // Y2K38
-}
-
-//
-// Steam/config/libraryfolders.vdf
-//
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamLibraryFolder {
-	#[serde(alias = "path")]
-	path: String,
-	//label: String,
-	//contentid: i64,
-	//totalsize: u64,
-	//update_clean_bytes_tally: u64,
-	//time_last_update_verified: u64,
-	//#[serde(alias = "apps")]
-	//apps: SteamLibraryFolderApps
-}
-
-//#[derive(Deserialize, Debug)]
-//struct SteamLibraryFolderApps {
-//	#[serde(rename = "4000")]
-//	gmod: Option<u64>
-//}
-
-//
-// SteamLibrary/appmanifest_4000.acf
-//
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamAppManifest {
-	//appid: u64,
-	//universe: u8, // 0-5
-	//launcher_path: String,
-	//name: String,
-	#[serde(alias = "stateflags")]
-	state_flags: u32, // https://github.com/SteamDatabase/SteamTracking/blob/master/Structs/EAppState.json
-	#[serde(alias = "installdir")]
-	install_dir: String,
-	//last_updated: u64,
-	//last_played: u64,
-	//size_on_disk: u64,
-	//buildid: u32,
-	//last_owner: u64,
-	//download_type: u32, // TODO: Is this right? Can't find documentation anywhere
-	//update_result: u32, // TODO: Is this right? Can't find documentation anywhere
-	#[serde(alias = "bytestodownload", default)]
-	bytes_to_download: u64,
-	#[serde(alias = "bytesdownloaded", default)]
-	bytes_downloaded: u64,
-	#[serde(alias = "bytestostage", default)]
-	bytes_to_stage: u64,
-	#[serde(alias = "bytesstaged", default)]
-	bytes_staged: u64,
-	//target_build_id: u32,
-	//auto_update_behavior: u8, // 1-3
-	//allow_other_downloads_while_running: bool,
-	#[serde(alias = "scheduledautoupdate")]
-	scheduled_auto_update: u64, // Y2K38
-	#[serde(alias = "fullvalidatebeforenextupdate")]
-	full_validate_before_next_update: Option<bool>,
-	//full_validate_after_next_update: bool,
-	//installed_depots: ,
-	//shared_depots: ,
-	//user_config: SteamAppConfig,
-	#[serde(alias = "mountedconfig")]
-	mounted_config: SteamAppConfig
-}
-
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamAppConfig {
-	#[serde(alias = "betakey")]
-	beta_key: Option<String>,
-	//language: Option<String>
-}
-
-//
-// Steam/config/config.vdf
-//
-#[cfg(target_os = "linux")]
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamConfig {
-	#[serde(alias = "software")]
-	software: SteamConfigSoftware
-	// Several entries unimplemented!
-}
-
-#[cfg(target_os = "linux")]
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamConfigSoftware {
-	#[serde(alias = "valve")]
-	valve: SteamConfigValve
-}
-
-#[cfg(target_os = "linux")]
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamConfigValve {
-	#[serde(alias = "steam")]
-	steam: SteamConfigSteam
-}
-
-#[cfg(target_os = "linux")]
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamConfigSteam {
-	#[serde(alias = "compattoolmapping")]
-	compat_tool_mapping: Option<SteamConfigCompatToolMappingApps>
-	// Several entries unimplemented!
-}
-
-#[cfg(target_os = "linux")]
-#[derive(Deserialize, Debug)]
-struct SteamConfigCompatToolMappingApps {
-	#[serde(rename = "4000")]
-	gmod: Option<SteamCompatToolMapping>
-}
-
-#[cfg(target_os = "linux")]
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct SteamCompatToolMapping {
-	#[serde(alias = "name")]
-	name: String,
-	//config: ,
-	//priority:
-}
-
 //
 // Steam/userdata/<steamid u32>/config/localconfig.vdf
 //
@@ -300,10 +276,31 @@ struct SteamUserLocalConfigApp {
 	//playtime2wks: u16
 }
 
-fn terminal_write<W>(writer: fn() -> W, output: &str, newline: bool, color: Option<&str>)
+// What kind of message it is for --output json, since that doesn't have colors
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum MessageLevel {
+	Info,
+	Success,
+	Warning,
+	Error
+}
+
+fn terminal_write<W>(writer: fn() -> W, output: &str, newline: bool, color: Option<&str>, level: MessageLevel)
 where
 	W: std::io::Write + 'static
 {
+	// Free text becomes a message event, minus any blank lines or terminal control sequences
+	if output_format() == OutputFormat::Json {
+		let text = output.trim_matches(['\n', '\r']);
+
+		if !text.is_empty() && !text.starts_with('\x1B') {
+			emit(writer, false, Event::Message { level, text });
+		}
+
+		return;
+	}
+
 	if color.is_some() && COLOR_LOOKUP.contains_key(color.unwrap()) {
 		write!(writer(), "{}", COLOR_LOOKUP[color.unwrap()]).unwrap();
 	}
@@ -319,60 +316,438 @@ where
 	}
 }
 
-async fn get_http_response<W>(writer: fn() -> W, writer_is_interactive: bool, servers: &[&str], filename: &str) -> Option<Response>
+// Everything notable the tool reports
+// Both --output formats are generated from these, so they can't drift apart
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+	Message { level: MessageLevel, text: &'a str },
+	SteamInstallation { number: usize, path: &'a str, default: bool, gmod_installed: bool, users: Vec<ListedSteamUser>, libraries: Vec<ListedSteamLibrary> },
+	SteamPath { path: &'a str },
+	SteamUser { persona_name: &'a str, steam_id_64: &'a str, steam3_id: &'a str },
+	GmodLibrary { path: &'a str },
+	GmodInstall { number: usize, library: &'a str, build_id: u32, state_flags: u32, last_updated: u64, listed_by_steam: bool, selected: bool },
+	GmodAppState {
+		state_flags: u32,
+		scheduled_auto_update: u64,
+		full_validate_before_next_update: bool,
+		bytes_downloaded: u64,
+		bytes_to_download: u64,
+		bytes_staged: u64,
+		bytes_to_stage: u64
+	},
+	GmodBranch { branch: &'a str },
+	GmodBranches { branches: Vec<ListedGmodBranch> },
+	GmodPath { path: &'a str },
+	TargetPlatform { platform: &'a str, platform_masked: &'a str, compat_tool: &'a str },
+	CompatTool { name: &'a str, path: Option<&'a str>, version: Option<&'a str> },
+	LaunchOptions { launch_options: &'a str, items: Vec<String> },
+	LaunchOptionFinding { id: &'a str, severity: launch_options::Severity, message: &'a str, fix: &'a str },
+	CompatPrefix { path: &'a str, initialized: bool, version: Option<&'a str> },
+	LinuxRuntime { compat_tool: &'a str, container: &'a str, version: Option<&'a str> },
+	FileStatus { filename: &'a str, status: &'a str, description: &'a str },
+	FileStatusSkipped { filename: &'a str, reason: &'a str },
+	FileStatusFailed { filename: &'a str, error: &'a str },
+	DownloadStart { filename: &'a str },
+	DownloadFinish { filename: &'a str, cached: bool, size: Option<u64> },
+	DownloadProgress { filename: &'a str, progress: ProgressSnapshot, total: ProgressSnapshot },
+	DownloadFailed { filename: &'a str, error: &'a str },
+	PatchStart { filename: &'a str },
+	PatchFinish { filename: &'a str },
+	PatchFailed { filename: &'a str, error: &'a str },
+	RestoreStart { filename: &'a str },
+	RestoreFinish { filename: &'a str },
+	RestoreFailed { filename: &'a str, error: &'a str },
+	RunSummary { platform_masked: &'a str, compat_tool: &'a str, container: Option<&'a str>, warnings: &'a [String] },
+	Outcome { outcome: &'a str, error: Option<String> }
+}
+
+#[derive(Serialize, Debug)]
+struct ListedSteamUser {
+	steam_id_64: String,
+	account_name: String,
+	persona_name: String,
+	most_recent: bool
+}
+
+#[derive(Serialize, Debug)]
+struct ListedSteamLibrary {
+	path: String,
+	gmod_installed: bool
+}
+
+#[derive(Serialize, Debug)]
+struct ListedGmodBranch {
+	name: String,
+	build_id: u32,
+	description: Option<String>,
+	password_required: bool,
+	installed: bool
+}
+
+impl Event<'_> {
+	// Text output and its color, if the event has any
+	fn text(&self) -> Option<(String, Option<&'static str>)> {
+		match self {
+			Event::Message { text, .. } => Some((text.to_string(), None)),
+			Event::SteamInstallation { number, path, default, gmod_installed, users, libraries } => {
+				let mut text = format!("{number}. {path}{}{}", if *gmod_installed { " (Garry's Mod installed)" } else { "" }, if *default { " [Default]" } else { "" });
+
+				text += "\n\tUsers:";
+				for user in users {
+					text += format!("\n\t\t{} ({}){}", user.persona_name, user.steam_id_64, if user.most_recent { " [Most Recent]" } else { "" }).as_str();
+				}
+
+				if users.is_empty() {
+					text += "\n\t\tNone";
+				}
+
+				text += "\n\tLibraries:";
+				for library in libraries {
+					text += format!("\n\t\t{}{}", library.path, if library.gmod_installed { " [Garry's Mod]" } else { "" }).as_str();
+				}
+
+				if libraries.is_empty() {
+					text += "\n\t\tNone";
+				}
+
+				Some((text + "\n", None))
+			},
+			Event::SteamPath { path } => Some((format!("Steam Path: {path}\n"), None)),
+			Event::SteamUser { persona_name, steam_id_64, steam3_id } => Some((format!("Steam User: {persona_name} ({steam_id_64} / {steam3_id})\n"), None)),
+			Event::GmodLibrary { path } => Some((format!("GMod Steam Library: {path}\n"), None)),
+			Event::GmodInstall { number, library, build_id, state_flags, last_updated, listed_by_steam, selected } => {
+				Some((format!("\t{number}. {library} (Build {build_id} | State {state_flags} | Updated {}){}{}", describe_timestamp_age(*last_updated), if *listed_by_steam { " [Steam's Install]" } else { "" }, if *selected { " [Selected]" } else { "" }), None))
+			},
+			Event::GmodAppState { state_flags, scheduled_auto_update, full_validate_before_next_update, bytes_downloaded, bytes_to_download, bytes_staged, bytes_to_stage } => {
+				Some((format!("GMod App State: {state_flags} | {scheduled_auto_update} | {full_validate_before_next_update} | {bytes_downloaded}/{bytes_to_download} | {bytes_staged}/{bytes_to_stage} \n"), None))
+			},
+			Event::GmodBranch { branch } => Some((format!("GMod Beta Branch: {branch}\n"), None)),
+			Event::GmodBranches { branches } => {
+				let mut text = "GMod Beta Branches:".to_string();
+
+				for branch in branches {
+					text += format!("\n\t{} (Build {}){}{}{}", branch.name, branch.build_id, branch.description.as_ref().map(|description| format!(" - {description}")).unwrap_or_default(), if branch.password_required { " [Password Required]" } else { "" }, if branch.installed { " [Installed]" } else { "" }).as_str();
+				}
+
+				Some((text + "\n", None))
+			},
+			Event::GmodPath { path } => Some((format!("GMod Path: {path}\n"), None)),
+			Event::TargetPlatform { platform_masked, compat_tool, .. } => Some((format!("Target Platform: {platform_masked} ({compat_tool})\n"), None)),
+			Event::LaunchOptions { items, .. } => {
+				let mut text = "GMod Launch Options:".to_string();
+
+				for item in items {
+					text += format!("\n\t{item}").as_str();
+				}
+
+				if items.is_empty() {
+					text += "\n\tNone";
+				}
+
+				Some((text + "\n", None))
+			},
+			Event::LaunchOptionFinding { severity, message, fix, .. } => {
+				let (label, color) = match severity {
+					launch_options::Severity::Info => ("Note", None),
+					launch_options::Severity::Warning => ("Warning", Some("yellow")),
+					launch_options::Severity::Error => ("WARNING", Some("yellow"))
+				};
+
+				Some((format!("{label}: {message}\n\tTo fix: {fix}"), color))
+			},
+			Event::CompatTool { name, path, version } => Some((format!("Compat Tool: {name} | {} | {}\n", path.unwrap_or("Not Found"), version.unwrap_or("Unknown Version")), None)),
+			Event::LinuxRuntime { container, version, .. } => Some((format!("Steam Linux Runtime: {container} container | {}\n", version.unwrap_or("Unknown Version")), None)),
+			Event::CompatPrefix { path, initialized, version } => Some((format!("GMod Proton Prefix: {path} | {} | {}\n", if *initialized { "Initialized" } else { "Not Initialized" }, version.unwrap_or("Unknown Version")), None)),
+			Event::FileStatus { filename, description, .. } => Some((format!("\t{filename}: {description}"), None)),
+			Event::FileStatusSkipped { filename, reason } => Some((format!("\t{filename}: Skipping due to {reason}"), Some("yellow"))),
+			Event::FileStatusFailed { filename, error } => Some((format!("\t{filename}: {error}"), Some("red"))),
+			Event::DownloadStart { filename } => Some((format!("\tDownloading: {filename} ..."), None)),
+			Event::DownloadFinish { filename, cached: true, .. } => Some((format!("\tDownloaded (From Cache): {filename}"), None)),
+			Event::DownloadFinish { filename, size, .. } => {
+				let size_mib = size.unwrap_or_default() as f64 / 0x100000 as f64;
+				Some((format!("\tDownloaded [{size_mib:.2} MiB]: {filename}"), None))
+			},
+			Event::DownloadProgress { filename, progress, total } => Some((format!("\t{filename}: {} | Total: {}", progress.describe(), total.describe()), None)),
+			Event::DownloadFailed { filename, error } => Some((format!("\tFailed to Download: {filename} | {error}"), Some("red"))),
+			Event::PatchStart { filename } => Some((format!("\tPatching: {filename} ..."), None)),
+			Event::PatchFinish { filename } => Some((format!("\tPatched: {filename}"), None)),
+			Event::PatchFailed { filename, error } => Some((format!("\tFailed to Patch: {filename} | {error}"), Some("red"))),
+			Event::RestoreStart { filename } => Some((format!("\tRestoring: {filename} ..."), None)),
+			Event::RestoreFinish { filename } => Some((format!("\tRestored: {filename}"), None)),
+			Event::RestoreFailed { filename, error } => Some((format!("\tFailed to Restore: {filename} | {error}"), Some("red"))),
+			Event::RunSummary { platform_masked, compat_tool, container, warnings } => {
+				let mut text = format!("Run Summary:\n\tTarget Platform: {platform_masked} ({compat_tool})\n\tContainer: {}", container.unwrap_or("None"));
+
+				for warning in warnings.iter() {
+					text += format!("\n\tWarning: {warning}").as_str();
+				}
+
+				Some((text + "\n", if warnings.is_empty() { None } else { Some("yellow") }))
+			},
+			// Errors are already logged on their own in text mode
+			Event::Outcome { .. } => None
+		}
+	}
+}
+
+fn emit<W>(writer: fn() -> W, writer_is_interactive: bool, event: Event)
+where
+	W: std::io::Write + 'static
+{
+	if output_format() == OutputFormat::Json {
+		// One write per event, so lines from other threads can't interleave
+		let event_json = serde_json::to_string(&event).unwrap();
+		writeln!(writer(), "{event_json}").unwrap();
+	} else if let Some((text, color)) = event.text() {
+		terminal_write(writer, text.as_str(), true, if writer_is_interactive { color } else { None }, MessageLevel::Info);
+	}
+}
+
+// A file we've started getting from one of the mirrors
+struct OpenedFile<'a> {
+	mirror: &'a MirrorRoot,
+	// Index into the mirrors that were passed to open_file_stream
+	mirror_id: usize,
+	stream: download::FileStream,
+	// Where the stream starts; 0 if we asked to resume but the mirror can't do that
+	offset: u64,
+	// Full size of the file, if the mirror told us
+	size: Option<u64>
+}
+
+// Tries each mirror in order until one of them has the file
+// If offset is set, asks the mirror to start there (HTTP Range) so partial downloads can be resumed
+// size is the full size of the file (if the manifest has it), for the request deadline
+async fn open_file_stream<'a, W>(writer: fn() -> W, writer_is_interactive: bool, downloader: &download::Downloader, mirrors: &'a [MirrorRoot], filename: &str, offset: u64, size: Option<u64>) -> Option<OpenedFile<'a>>
 where
 	W: std::io::Write + 'static
 {
-	let mut server_id: u8 = 0;
+	let mut offset = offset;
+	let mut mirror_id: usize = 0;
 	let mut try_count: u8 = 0;
-	let mut response = None;
-	while (server_id as usize) < servers.len() {
-		let url = servers[server_id as usize].to_string() + filename;
-
-		let client = reqwest::Client::builder()
-			.connect_timeout(std::time::Duration::new(10, 0)) // Initial connection failure
-			.read_timeout(std::time::Duration::new(10, 0)) // Stall detection
-			//.timeout(std::time::Duration::new(size, 0)) // TODO: Total DEADLINE timeout (downloading too slow)
-			.build();
-
-		let response_result = match client {
-			Ok(client) => client.get(url.clone()).send().await,
-			Err(error) => Err(error)
-		};
+	while mirror_id < mirrors.len() {
+		let url = mirrors[mirror_id].get_file_location(filename);
+
+		if let MirrorRoot::Local(_) = mirrors[mirror_id] {
+			match download::open_local_file(&url, offset).await {
+				Ok((file, offset, size)) => {
+					return Some(OpenedFile {
+						mirror: &mirrors[mirror_id],
+						mirror_id,
+						stream: download::FileStream::Local(file),
+						offset,
+						size: Some(size)
+					});
+				},
+				Err(error) => {
+					terminal_write(writer, format!("\n{url}\n\tFile Error: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+					mirror_id += 1;
+					continue;
+				}
+			}
+		}
+
+		let mut request = downloader.client.get(url.clone());
+		if offset > 0 {
+			request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+		}
+
+		if let Some(deadline) = downloader.get_deadline(size.map(|size| size.saturating_sub(offset))) {
+			request = request.timeout(deadline);
+		}
+
+		let response_result = request.send().await;
 
 		match response_result {
-			Ok(response_unwrapped) => {
-				let response_status_code = response_unwrapped.status().as_u16();
-				if response_status_code == 200 {
-					response = Some(response_unwrapped);
-					break;
-				} else {
-					terminal_write(writer, format!("\n{url}\n\tBad HTTP Status Code: {response_status_code}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
-					response = None;
-					server_id += 1;
-					try_count = 0;
+			Ok(response) => {
+				let response_status_code = response.status().as_u16();
+				match response_status_code {
+					200 => {
+						// Either we asked for the whole file, or the mirror ignored the Range
+						let size = response.content_length();
+
+						return Some(OpenedFile {
+							mirror: &mirrors[mirror_id],
+							mirror_id,
+							stream: download::FileStream::Http(response),
+							offset: 0,
+							size
+						});
+					},
+					206 => {
+						let content_range = response.headers().get(reqwest::header::CONTENT_RANGE)
+							.and_then(|content_range| content_range.to_str().ok())
+							.and_then(download::parse_content_range);
+
+						match content_range {
+							Some((start, size)) if start == offset => {
+								return Some(OpenedFile {
+									mirror: &mirrors[mirror_id],
+									mirror_id,
+									stream: download::FileStream::Http(response),
+									offset,
+									size
+								});
+							},
+							_ => {
+								// Not the range we asked for, so just get the whole thing
+								offset = 0;
+							}
+						}
+					},
+					416 if offset > 0 => {
+						// Our partial file is at least as big as the real one, so it isn't from this file
+						offset = 0;
+					},
+					_ => {
+						terminal_write(writer, format!("\n{url}\n\tBad HTTP Status Code: {response_status_code}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+
+						// 429/503 can tell us how long to wait
+						try_count += 1;
+						let retry_decision = download::get_retry_decision(try_count, download::is_transient_http_status(response_status_code), download::get_retry_after(&response));
+						wait_for_retry(writer, retry_decision, &mut mirror_id, &mut try_count).await;
+					}
 				}
 			},
 			Err(error) => {
+				let transient = download::is_transient_http_error(&error);
 				let error = error.without_url();
-				terminal_write(writer, format!("\n{url}\n\tHTTP Error: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
-				response = None;
+				terminal_write(writer, format!("\n{url}\n\tHTTP Error: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+
 				try_count += 1;
+				wait_for_retry(writer, download::get_retry_decision(try_count, transient, None), &mut mirror_id, &mut try_count).await;
+			}
+		}
+	}
+
+	None
+}
+
+// Either waits out the backoff before trying the same mirror again, or moves on to the next one
+async fn wait_for_retry<W>(writer: fn() -> W, retry_decision: download::RetryDecision, mirror_id: &mut usize, try_count: &mut u8)
+where
+	W: std::io::Write + 'static
+{
+	match retry_decision {
+		download::RetryDecision::Retry(delay) => {
+			terminal_write(writer, format!("\tRetrying in {:.1} second(s)...", delay.as_secs_f64()).as_str(), true, None, MessageLevel::Info);
+			tokio::time::sleep(delay).await;
+		},
+		download::RetryDecision::NextMirror => {
+			*mirror_id += 1;
+			*try_count = 0;
+		}
+	}
+}
+
+// Gets a whole (small) file into memory
+// Also returns the index of the mirror it came from
+// open_file_stream already retries each mirror, so if one breaks off partway through, we just move on to the next
+async fn get_file_bytes<W>(writer: fn() -> W, writer_is_interactive: bool, downloader: &download::Downloader, mirrors: &[MirrorRoot], filename: &str) -> Option<(usize, Vec<u8>)>
+where
+	W: std::io::Write + 'static
+{
+	let mut mirror_id: usize = 0;
+	while mirror_id < mirrors.len() {
+		let mut opened = open_file_stream(writer, writer_is_interactive, downloader, &mirrors[mirror_id..], filename, 0, None).await?;
+		mirror_id += opened.mirror_id;
+
+		let mut bytes = Vec::new();
+		let read_result = loop {
+			match opened.stream.next_chunk().await {
+				Ok(Some(chunk)) => {
+					downloader.throttle(chunk.len()).await;
+					bytes.extend_from_slice(&chunk);
+				},
+				Ok(None) => break Ok(()),
+				Err(error) => break Err(error)
+			}
+		};
+
+		match read_result {
+			Ok(_) => return Some((mirror_id, bytes)),
+			Err(error) => {
+				let url = opened.mirror.get_file_location(filename);
+				terminal_write(writer, format!("\n{url}\n\tDownload Error: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+
+				mirror_id += 1;
+			}
+		}
+	}
+
+	None
+}
+
+// For files that decide what gets written into the GMod install (manifest.json, version.txt)
+// The signature has to come from the same mirror as the file, so a bad mirror can't be covered for by a good one
+// A mirror with an unsigned, badly signed, or older file than we've already used is skipped for the next one
+async fn get_signed_file_bytes<W>(writer: fn() -> W, writer_is_interactive: bool, downloader: &download::Downloader, mirrors: &[MirrorRoot], filename: &str) -> Result<Option<Vec<u8>>, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let signed_at_path = signing::get_signed_at_path();
+	let mut signed_at = signed_at_path.as_deref().map(signing::read_signed_at).unwrap_or_default();
+	let last_signed_at = signed_at.get(filename).copied().unwrap_or_default();
+
+	let signature_filename = format!("{filename}{}", signing::SIGNATURE_EXTENSION);
+
+	let mut last_error = None;
+	let mut mirror_id: usize = 0;
+	while mirror_id < mirrors.len() {
+		let file = get_file_bytes(writer, writer_is_interactive, downloader, &mirrors[mirror_id..], filename).await;
+		if file.is_none() {
+			break;
+		}
+
+		let (file_mirror_id, bytes) = file.unwrap();
+		let mirror = &mirrors[mirror_id + file_mirror_id];
+		let url = mirror.get_file_location(filename);
+		mirror_id += file_mirror_id + 1;
+
+		let verify_result = match get_file_bytes(writer, writer_is_interactive, downloader, std::slice::from_ref(mirror), signature_filename.as_str()).await {
+			Some((_, signature)) => match signing::verify(&bytes, String::from_utf8_lossy(&signature).as_ref()) {
+				Ok(file_signed_at) if file_signed_at < last_signed_at => Err("is older than one we've already used".to_string()),
+				Ok(file_signed_at) => Ok(file_signed_at),
+				Err(error) => Err(format!("has a bad signature ({error})"))
+			},
+			None => Err("isn't signed".to_string())
+		};
+
+		match verify_result {
+			Ok(file_signed_at) => {
+				if file_signed_at > last_signed_at && let Some(signed_at_path) = &signed_at_path {
+					signed_at.insert(filename.to_string(), file_signed_at);
+
+					if let Err(error) = signing::write_signed_at(signed_at_path, &signed_at) {
+						terminal_write(writer, format!("Warning: Couldn't save when {filename} was signed: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+					}
+				}
+
+				return Ok(Some(bytes));
+			},
+			Err(error) => {
+				let error = format!("{url} {error}! Refusing to use it.\nThis mirror may be out of date or compromised; please report it to us.");
 
-				// Try each server 3 times for full HTTP errors (Anti-DDoS, etc)
-				if try_count >= 3 {
-					server_id += 1;
-					try_count = 0;
+				// The last one is reported by whoever called us
+				if mirror_id < mirrors.len() {
+					terminal_write(writer, format!("\n{error}\n\tTrying the next mirror...").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
 				}
+
+				last_error = Some(error);
 			}
 		}
 	}
 
-	response
+	// Only an error if a mirror had it, but none of them had a copy we could trust
+	match last_error {
+		Some(error) => Err(AlmightyError::Generic(error)),
+		None => Ok(None)
+	}
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum IntegrityStatus {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum IntegrityStatus {
 	NeedDelete = 0,
 	NeedOriginal = 1,
 	NeedWipeFix = 2,
@@ -409,98 +784,395 @@ fn determine_file_integrity_status(gmod_path: PathBuf, filename: &str, hashes: &
 	}
 }
 
-async fn download_file_to_cache<W>(writer: fn() -> W, writer_is_interactive: bool, cache_dir: PathBuf, filename: String, target_hash: String) -> Result<(), ()>
+enum StreamError {
+	// Connection dropped, etc; we can resume from the .part file
+	Remote(String),
+	// Disk errors and corrupt downloads; no point in retrying
+	Local(String)
+}
+
+// Streams a download into its .part file, decompressing and hashing it as it comes in
+// Returns the hash of the (decompressed) file, and the size of the download
+#[allow(clippy::too_many_arguments)]
+async fn stream_file_to_part<W>(
+	writer: fn() -> W,
+	writer_is_interactive: bool,
+	downloader: &download::Downloader,
+	opened: &mut OpenedFile<'_>,
+	filename: &str,
+	part_file_path: &Path,
+	decompressed_file_path: Option<&Path>,
+	total_progress: &TotalProgress
+) -> Result<(String, u64), StreamError>
 where
 	W: std::io::Write + 'static
 {
-	let filename_no_zst = if filename.ends_with(".zst") {
-		let len = filename.len() - 4;
-		filename[..len].to_string()
-	} else {
-		filename.clone()
-	};
-	let file_parts: Vec<&str> = filename_no_zst.split("/").collect();
-	let cache_file_path = extend_pathbuf_and_return(cache_dir, &file_parts[..]);
-	let cache_file_path_result = pathbuf_to_canonical_pathbuf(cache_file_path.clone(), false);
+	// If the mirror couldn't resume, this truncates the .part file so we start over
+	let part_file = std::fs::OpenOptions::new().create(true).write(true).truncate(false).open(part_file_path)
+		.and_then(|part_file| part_file.set_len(opened.offset).map(|_| part_file))
+		.map_err(|error| StreamError::Local(format!("Step 2: {error}")))?;
+
+	let mut sink = download::CacheFileSink::new(decompressed_file_path).map_err(|error| StreamError::Local(format!("Step 2: {error}")))?;
+	if opened.offset > 0 {
+		sink.write_file(part_file_path).map_err(|error| StreamError::Local(format!("Decompress: {error}")))?;
+	}
 
-	terminal_write(writer, format!("\tDownloading: {filename} ...").as_str(), true, None);
+	let mut part_file = io::BufWriter::new(part_file);
+	part_file.seek(io::SeekFrom::End(0)).map_err(|error| StreamError::Local(format!("Step 2: {error}")))?;
 
-	// Look in the cache to see if the file already exists
-	if cache_file_path_result.is_ok() {
-		let file_hash_result = get_file_hash(&cache_file_path);
+	let mut file_progress = FileProgress::new(opened.offset, opened.size);
+	loop {
+		let chunk = opened.stream.next_chunk().await;
 
-		if let Ok(file_hash) = file_hash_result {
-			if file_hash == target_hash {
-				terminal_write(writer, format!("\tDownloaded (From Cache): {filename}").as_str(), true, None);
-				return Ok(());
+		// Make sure the .part file only has complete chunks in it before we try to resume
+		let chunk = match chunk {
+			Ok(Some(chunk)) => chunk,
+			Ok(None) => break,
+			Err(error) => {
+				part_file.flush().map_err(|error| StreamError::Local(format!("Step 2: {error}")))?;
+				return Err(StreamError::Remote(error));
 			}
+		};
+
+		downloader.throttle(chunk.len()).await;
+
+		part_file.write_all(&chunk).map_err(|error| StreamError::Local(format!("Step 2: {error}")))?;
+		sink.write_all(&chunk).map_err(|error| StreamError::Local(format!("Decompress: {error}")))?;
+
+		total_progress.add_transferred(chunk.len() as u64);
+		if file_progress.add(chunk.len() as u64) {
+			emit(writer, writer_is_interactive, Event::DownloadProgress { filename, progress: file_progress.snapshot(), total: total_progress.snapshot() });
+		}
+	}
+
+	let part_file = part_file.into_inner().map_err(|error| StreamError::Local(format!("Step 2: {}", error.error())))?;
+	part_file.sync_all().map_err(|error| StreamError::Local(format!("Step 2: {error}")))?;
+
+	let file_hash = sink.finish().map_err(|error| StreamError::Local(format!("Step 3: {error}")))?;
+
+	Ok((file_hash, file_progress.snapshot().transferred))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn download_file_to_cache<W>(
+	writer: fn() -> W,
+	writer_is_interactive: bool,
+	downloader: Arc<download::Downloader>,
+	mirrors: Vec<MirrorRoot>,
+	cache_dir: PathBuf,
+	filename: String,
+	target_hash: String,
+	size: Option<u64>,
+	total_progress: Arc<TotalProgress>
+) -> Result<(), ()>
+where
+	W: std::io::Write + 'static
+{
+	let is_zst = filename.ends_with(".zst");
+	let filename_no_zst = if is_zst {
+		let len = filename.len() - 4;
+		filename[..len].to_string()
+	} else {
+		filename.clone()
+	};
+	let file_parts: Vec<&str> = filename_no_zst.split("/").collect();
+	let cache_file_path = extend_pathbuf_and_return(cache_dir, &file_parts[..]);
+	let cache_file_path_result = pathbuf_to_canonical_pathbuf(cache_file_path.clone(), false);
+
+	emit(writer, writer_is_interactive, Event::DownloadStart { filename: &filename });
+
+	// Look in the cache to see if the file already exists
+	if cache_file_path_result.is_ok() {
+		let file_hash_result = get_file_hash(&cache_file_path);
+
+		if let Ok(file_hash) = file_hash_result {
+			if file_hash == target_hash {
+				total_progress.remove_expected(size.unwrap_or_default());
+				emit(writer, writer_is_interactive, Event::DownloadFinish { filename: &filename, cached: true, size: None });
+				return Ok(());
+			}
+		}
+	}
+
+	// Create directories if needed
+	let mut cache_file_path_dir = cache_file_path.clone();
+	cache_file_path_dir.pop();
+	let cache_file_path_dir_canonical = pathbuf_to_canonical_pathbuf(cache_file_path_dir.clone(), false);
+
+	if cache_file_path_dir_canonical.is_err() {
+		let create_dir_result = tokio::fs::create_dir_all(cache_file_path_dir).await;
+
+		if let Err(error) = create_dir_result {
+			total_progress.remove_expected(size.unwrap_or_default());
+			emit(writer, writer_is_interactive, Event::DownloadFailed { filename: &filename, error: format!("Step 2: {error}").as_str() });
+			return Err(());
 		}
 	}
 
 	// If it's not in the cache, or there's a checksum mismatch with the version in the cache, (re-)download it
-	let response = get_http_response(writer, writer_is_interactive, &BINARY_SERVER_ROOTS, filename.as_str()).await;
-	if let Some(response) = response {
-		let bytes_raw = response.bytes().await;
-
-		match bytes_raw {
-			Ok(bytes_raw) => {
-				// Create directories if needed
-				let mut cache_file_path_dir = cache_file_path.clone();
-				cache_file_path_dir.pop();
-				let cache_file_path_dir_canonical = pathbuf_to_canonical_pathbuf(cache_file_path_dir.clone(), false);
-
-				if cache_file_path_dir_canonical.is_err() {
-					let create_dir_result = tokio::fs::create_dir_all(cache_file_path_dir).await;
-
-					if let Err(error) = create_dir_result {
-						terminal_write(writer, format!("\tFailed to Download: {filename} | Step 2: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
-						return Err(());
-					}
-				}
+	// Files from local mirrors and offline bundles go through the cache too, so they get the same checks
+	// The (still compressed) download goes into a .part file first, which is resumed if we lose the connection, even across runs
+	let mut part_file_path = cache_file_path.clone().into_os_string();
+	part_file_path.push(if is_zst { ".zst" } else { "" });
+	part_file_path.push(download::PART_FILE_SUFFIX);
+	let part_file_path = PathBuf::from(part_file_path);
+	let decompressed_file_path = if is_zst { Some(journal::get_temp_file_path(&cache_file_path)) } else { None };
+
+	// Whatever's left over from a previous run doesn't need to be downloaded again, but only once a mirror agrees to resume it
+	let mut previous_part_file_len = std::fs::metadata(&part_file_path).map(|metadata| metadata.len()).unwrap_or_default();
+
+	// A dropped .part has to be downloaded again, including anything we transferred into it
+	let drop_part_file = |part_file_len: u64, previous_part_file_len: &mut u64| {
+		total_progress.add_expected(part_file_len.saturating_sub(*previous_part_file_len));
+		*previous_part_file_len = 0;
+	};
+
+	// Only download a few files at a time
+	let _download_slot = downloader.acquire_download_slot().await;
+
+	let mut expected_size = size;
+	let mut mirror_id: usize = 0;
+	let mut try_count: u8 = 0;
+	let mut error = "Step 1: Couldn't get the file from any mirror".to_string();
+	while mirror_id < mirrors.len() {
+		let part_file_len = std::fs::metadata(&part_file_path).map(|metadata| metadata.len()).unwrap_or_default();
+		let opened = open_file_stream(writer, writer_is_interactive, &downloader, &mirrors[mirror_id..], filename.as_str(), part_file_len, size).await;
+		if opened.is_none() {
+			break;
+		}
+
+		let mut opened = opened.unwrap();
+		if opened.mirror_id > 0 {
+			mirror_id += opened.mirror_id;
+			try_count = 0;
+		}
+
+		// Older manifests don't have sizes, so count it once the mirror tells us
+		if expected_size.is_none() && let Some(opened_size) = opened.size {
+			total_progress.add_expected(opened_size);
+			expected_size = Some(opened_size);
+		}
 
-				// Decompress Zstandard files
-				let mut bytes: Vec<u8> = if filename.ends_with(".zst") { Vec::new() } else { bytes_raw.to_vec() };
-				if filename.ends_with(".zst") {
-					terminal_write(writer, format!("\tDecompressing: {filename} ...").as_str(), true, None);
+		if opened.offset > 0 {
+			total_progress.remove_expected(previous_part_file_len);
+			previous_part_file_len = 0;
+		} else if part_file_len > 0 {
+			// The mirror sent the whole file, so the .part is started over
+			drop_part_file(part_file_len, &mut previous_part_file_len);
+		}
+
+		let url = opened.mirror.get_file_location(filename.as_str());
+		let stream_result = stream_file_to_part(writer, writer_is_interactive, &downloader, &mut opened, filename.as_str(), &part_file_path, decompressed_file_path.as_deref(), &total_progress).await;
 
-					let decompress_result = zstd::stream::copy_decode(&bytes_raw[..], &mut bytes);
-					if let Err(error) = decompress_result {
-						terminal_write(writer, format!("\tFailed to Decompress: {filename} | {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+		match stream_result {
+			Ok((file_hash, download_size)) => {
+				if file_hash == target_hash {
+					let commit_result = match &decompressed_file_path {
+						Some(decompressed_file_path) => std::fs::rename(decompressed_file_path, &cache_file_path).and_then(|_| std::fs::remove_file(&part_file_path)),
+						None => std::fs::rename(&part_file_path, &cache_file_path)
+					};
+
+					if let Err(error) = commit_result {
+						emit(writer, writer_is_interactive, Event::DownloadFailed { filename: &filename, error: format!("Step 2: {error}").as_str() });
 						return Err(());
 					}
 
-					terminal_write(writer, format!("\tDecompressed: {filename}").as_str(), true, None);
+					emit(writer, writer_is_interactive, Event::DownloadFinish { filename: &filename, cached: false, size: Some(download_size) });
+					return Ok(());
 				}
 
-				let write_result = tokio::fs::write(cache_file_path.clone(), bytes).await;
-				if let Err(error) = write_result {
-					terminal_write(writer, format!("\tFailed to Download: {filename} | Step 2: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
-					return Err(());
-				}
+				// Don't keep a bad download around to resume from
+				drop_part_file(download_size, &mut previous_part_file_len);
+				let _ = std::fs::remove_file(&part_file_path);
+				error = "Step 4: Checksum mismatch".to_string();
 
-				let file_hash_result = get_file_hash(&cache_file_path);
-				match file_hash_result {
-					Ok(file_hash) => {
-						if file_hash == target_hash {
-							let size_mib = bytes_raw.len() as f64 / 0x100000 as f64;
-							terminal_write(writer, format!("\tDownloaded [{size_mib:.2} MiB]: {filename}").as_str(), true, None);
-							return Ok(());
-						} else {
-							terminal_write(writer, format!("\tFailed to Download: {filename} | Step 4: Checksum mismatch").as_str(), true, if writer_is_interactive { Some("red") } else { None });
-						}
+				// If we resumed, the old part may have been the problem, so give it one more go from scratch
+				if opened.offset == 0 {
+					break;
+				}
+			},
+			Err(download_error) => {
+				match download_error {
+					StreamError::Remote(download_error) => {
+						terminal_write(writer, format!("\n{url}\n\tDownload Error: {download_error}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+
+						// Dropped connections and deadlines are resumed where we left off
+						try_count += 1;
+						wait_for_retry(writer, download::get_retry_decision(try_count, true, None), &mut mirror_id, &mut try_count).await;
 					},
-					Err(error) => {
-						terminal_write(writer, format!("\tFailed to Download: {filename} | Step 3: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+					StreamError::Local(download_error) => {
+						// Might be a corrupt .part file, so start over next time
+						drop_part_file(std::fs::metadata(&part_file_path).map(|metadata| metadata.len()).unwrap_or_default(), &mut previous_part_file_len);
+						let _ = std::fs::remove_file(&part_file_path);
+						error = download_error;
+						break;
 					}
 				}
+			}
+		}
+	}
+
+	if let Some(decompressed_file_path) = &decompressed_file_path {
+		let _ = std::fs::remove_file(decompressed_file_path);
+	}
+
+	// Only what's still left to download; whatever we did transfer stays counted
+	let part_file_len = std::fs::metadata(&part_file_path).map(|metadata| metadata.len()).unwrap_or_default();
+	total_progress.remove_expected(expected_size.unwrap_or_default().saturating_sub(part_file_len.saturating_sub(previous_part_file_len)));
+	emit(writer, writer_is_interactive, Event::DownloadFailed { filename: &filename, error: error.as_str() });
+
+	Err(())
+}
+
+// Archived offline bundles only have what we're about to download unpacked
+fn extract_from_offline_bundle(offline_bundle: Option<&bundle::OfflineBundle>, downloads: &[(String, String, Option<u64>)]) -> Result<(), AlmightyError> {
+	let Some(offline_bundle) = offline_bundle else {
+		return Ok(());
+	};
+
+	let filenames: Vec<String> = downloads.iter().map(|(filename, _, _)| filename.clone()).collect();
+
+	offline_bundle.extract(&filenames).map_err(AlmightyError::Generic)
+}
+
+// Figure out where our cache should go based on OS
+fn get_os_cache_dir() -> PathBuf {
+	if let Some(dirs_cache_dir) = dirs::cache_dir() { dirs_cache_dir } else { std::env::temp_dir() }
+}
+
+// Checks if a file is already in the cache without creating anything
+fn is_file_cached(cache_dir: &Path, filename: &str, target_hash: &str) -> bool {
+	let file_parts: Vec<&str> = filename.split("/").collect();
+	let cache_file_path = extend_pathbuf_and_return(cache_dir.to_path_buf(), &file_parts[..]);
+
+	cache_file_path.is_file() && get_file_hash(&cache_file_path).is_ok_and(|file_hash| file_hash == target_hash)
+}
+
+// What a run would download, for --dry-run
+#[derive(Default)]
+struct DownloadPlan {
+	files: usize,
+	bytes: u64,
+	unknown_size_files: usize,
+	cached_files: usize
+}
+
+impl DownloadPlan {
+	// filename is the path in the cache, so without .zst
+	fn add(&mut self, cache_dir: Option<&Path>, filename: &str, target_hash: &str, size: Option<u64>) {
+		if let Some(cache_dir) = cache_dir && is_file_cached(cache_dir, filename, target_hash) {
+			self.cached_files += 1;
+			return;
+		}
+
+		self.files += 1;
+
+		// Older manifests don't have sizes
+		match size {
+			Some(size) => self.bytes += size,
+			None => self.unknown_size_files += 1
+		}
+	}
+
+	fn describe(&self) -> String {
+		let size_mib = self.bytes as f64 / 0x100000 as f64;
+		let mut description = format!("{} file(s), {size_mib:.2} MiB", self.files);
+
+		if self.unknown_size_files > 0 {
+			description += format!(" (+{} of unknown size)", self.unknown_size_files).as_str();
+		}
+
+		if self.cached_files > 0 {
+			description += format!(" | {} already cached", self.cached_files).as_str();
+		}
+
+		description
+	}
+}
+
+// Size of the file we download (so compressed, for originals), if the manifest is new enough to have it
+fn get_manifest_size(hashes: &IndexMap<String, String>, size_key: &str) -> Option<u64> {
+	hashes.get(size_key).and_then(|size| size.parse().ok())
+}
+
+// Where the cache would be for --dry-run, without creating it
+fn get_dry_run_cache_dir(disable_cache: bool) -> Option<PathBuf> {
+	// --disable-cache clears it first, so nothing in it counts
+	if disable_cache {
+		return None;
+	}
+
+	pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(get_os_cache_dir(), &["GModPatchTool"]), false).ok()
+}
+
+async fn get_cache_dir<W>(writer: fn() -> W, writer_is_interactive: bool, disable_cache: bool) -> Result<PathBuf, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let os_cache_dir = get_os_cache_dir();
+
+	// Delete old GModCEFCodecFix cache directory
+	#[cfg(windows)]
+	let old_cache_dir = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(os_cache_dir.clone(), &["Temp", "GModCEFCodecFix"]), false);
+
+	#[cfg(not(windows))]
+	let old_cache_dir = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(os_cache_dir.clone(), &["GModCEFCodecFix"]), false);
+
+	if let Ok(old_cache_dir) = old_cache_dir {
+		let old_cache_dir_result = tokio::fs::remove_dir_all(old_cache_dir).await;
+
+		match old_cache_dir_result {
+			Ok(_) => {
+				terminal_write(writer,"Successfully removed old GModCEFCodecFix cache directory.", true, None, MessageLevel::Info);
 			},
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Download: {filename} | Step 1: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				terminal_write(writer, format!("Failed to remove old GModCEFCodecFix cache directory: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
 			}
 		}
 	}
 
-	Err(())
+	// Create new GModPatchTool cache directory if it doesn't exist
+	let cache_path = extend_pathbuf_and_return(os_cache_dir, &["GModPatchTool"]);
+	let mut cache_path_str = cache_path.to_string_lossy();
+	let mut cache_dir = pathbuf_to_canonical_pathbuf(cache_path.clone(), false);
+
+	// ...but make sure it doesn't exist (and clear it) if disable_cache is set
+	if disable_cache {
+		if let Ok(cache_dir) = cache_dir {
+			let remove_result = tokio::fs::remove_dir_all(cache_dir).await;
+
+			match remove_result {
+				Ok(_) => {
+					terminal_write(writer,"\n[disable-cache:Pre] Successfully cleared GModPatchTool cache directory.", true, None, MessageLevel::Info);
+				},
+				Err(error) => {
+					terminal_write(writer, format!("\n[disable-cache:Pre] Failed to clear GModPatchTool cache directory: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+				}
+			}
+		}
+
+		cache_dir = pathbuf_to_canonical_pathbuf(cache_path.clone(), false);
+	}
+
+	if cache_dir.is_err() {
+		let create_result = tokio::fs::create_dir(cache_path.clone()).await;
+
+		if create_result.is_ok() {
+			cache_dir = pathbuf_to_canonical_pathbuf(cache_path.clone(), false);
+		}
+	}
+
+	// Can't access or create the cache directory!
+	if let Err(error) = cache_dir {
+		return Err(AlmightyError::Generic(format!("Failed to create cache directory ({error}):\n\t{cache_path_str}")));
+	}
+
+	let cache_dir = cache_dir.unwrap();
+	cache_path_str = cache_dir.to_string_lossy();
+
+	terminal_write(writer, format!("\nGModPatchTool Cache Directory: {cache_path_str}\n").as_str(), true, None, MessageLevel::Info);
+
+	Ok(cache_dir)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -512,6 +1184,7 @@ fn patch_file<W>(
 	platform_masked: &str,
 	gmod_branch: &String,
 	cache_dir: &Path,
+	journal: &journal::Journal,
 	filename: &&String,
 	integrity_status: &IntegrityStatus,
 	hashes: &&IndexMap<String, String>
@@ -519,25 +1192,36 @@ fn patch_file<W>(
 where
 	W: std::io::Write + 'static
 {
-	terminal_write(writer, format!("\tPatching: {filename} ...").as_str(), true, None);
+	emit(writer, writer_is_interactive, Event::PatchStart { filename });
 
 	let mut new_integrity_status: IntegrityStatus = *integrity_status;
 	let mut integrity_status_string = integrity_status_strings[&new_integrity_status];
 	let gmod_file_parts: Vec<&str> = filename.split("/").collect();
 	let gmod_file_path = extend_pathbuf_and_return(gmod_path.to_path_buf(), &gmod_file_parts[..]);
 
+	// Every step is journaled before moving on, so an interrupted run can be resumed or rolled back
+	if let Err(error) = journal.record_start(filename, new_integrity_status) {
+		emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Journal: {error}").as_str() });
+		return new_integrity_status;
+	}
+
 	// Delete the file since it's not used anymore
 	// If we can't delete it outright, try and truncate it
 	// We could alternatively "patch" it into being empty...but that's a waste of CPU cycles, and if truncating doesn't work, that won't work either
 	if new_integrity_status == IntegrityStatus::NeedDelete {
 		if let Err(delete_error) = std::fs::remove_file(&gmod_file_path) {
 			if let Err(truncate_error) = File::create(&gmod_file_path) {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string}:\n\tDelete: {delete_error}\n\tTruncate: {truncate_error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string}:\n\tDelete: {delete_error}\n\tTruncate: {truncate_error}").as_str() });
 				return new_integrity_status;
 			}
 		}
 
-		terminal_write(writer, format!("\tPatched: {filename}").as_str(), true, None);
+		if let Err(error) = journal.record_transition(filename, new_integrity_status, IntegrityStatus::Fixed) {
+			emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Journal: {error}").as_str() });
+			return new_integrity_status;
+		}
+
+		emit(writer, writer_is_interactive, Event::PatchFinish { filename });
 		new_integrity_status = IntegrityStatus::Fixed;
 		integrity_status_string = integrity_status_strings[&new_integrity_status];
 	}
@@ -550,10 +1234,15 @@ where
 
 		match original_cache_file_path {
 			Ok(original_cache_file_path) => {
-				let copy_result = std::fs::copy(original_cache_file_path, &gmod_file_path);
+				let copy_result = journal::copy_file_atomic(&original_cache_file_path, &gmod_file_path);
 
 				if let Err(error) = copy_result {
-					terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string}: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+					emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string}: {error}").as_str() });
+					return new_integrity_status;
+				}
+
+				if let Err(error) = journal.record_transition(filename, new_integrity_status, IntegrityStatus::NeedFix) {
+					emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Journal: {error}").as_str() });
 					return new_integrity_status;
 				}
 
@@ -561,7 +1250,7 @@ where
 				integrity_status_string = integrity_status_strings[&new_integrity_status];
 			},
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string}: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string}: {error}").as_str() });
 				return new_integrity_status;
 			}
 		}
@@ -576,7 +1265,7 @@ where
 			let create_dir_result = std::fs::create_dir_all(gmod_file_path_dir);
 
 			if let Err(error) = create_dir_result {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string}: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string}: {error}").as_str() });
 				return new_integrity_status;
 			}
 		}
@@ -584,7 +1273,12 @@ where
 		let create_result = File::create(&gmod_file_path);
 
 		if let Err(error) = create_result {
-			terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string}: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+			emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string}: {error}").as_str() });
+			return new_integrity_status;
+		}
+
+		if let Err(error) = journal.record_transition(filename, new_integrity_status, IntegrityStatus::NeedFix) {
+			emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Journal: {error}").as_str() });
 			return new_integrity_status;
 		}
 
@@ -597,7 +1291,7 @@ where
 		let gmod_file_path = match pathbuf_to_canonical_pathbuf(gmod_file_path, false) {
 			Ok(gmod_file_path) => gmod_file_path,
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 1: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 1: {error}").as_str() });
 				return new_integrity_status;
 			}
 		};
@@ -608,7 +1302,7 @@ where
 		let patch_file_path = match pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(cache_dir.to_path_buf(), &patch_file_parts[..]), false) {
 			Ok(patch_file_path) => patch_file_path,
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 2: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 2: {error}").as_str() });
 				return new_integrity_status;
 			}
 		};
@@ -616,7 +1310,7 @@ where
 		let gmod_file = match std::fs::read(gmod_file_path.clone()) {
 			Ok(gmod_file) => gmod_file,
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 3: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 3: {error}").as_str() });
 				return new_integrity_status;
 			}
 		};
@@ -624,7 +1318,7 @@ where
 		let patch_file = match std::fs::read(patch_file_path) {
 			Ok(patch_file) => patch_file,
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 4: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 4: {error}").as_str() });
 				return new_integrity_status;
 			}
 		};
@@ -632,7 +1326,7 @@ where
 		let patcher = match Bspatch::new(&patch_file) {
 			Ok(patcher) => patcher,
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 5: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 5: {error}").as_str() });
 				return new_integrity_status;
 			}
 		};
@@ -641,14 +1335,15 @@ where
 		let patch_result = patcher.apply(&gmod_file, io::Cursor::new(&mut new_gmod_file));
 
 		if let Err(error) = patch_result {
-			terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 6: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+			emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 6: {error}").as_str() });
 			return new_integrity_status;
 		}
 
-		let write_result = std::fs::write(&gmod_file_path, &new_gmod_file);
+		// Write next to the file and swap it in, so we never leave a half-written file behind
+		let write_result = journal::write_file_atomic(&gmod_file_path, &new_gmod_file);
 
 		if let Err(error) = write_result {
-			terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 7: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+			emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 7: {error}").as_str() });
 			return new_integrity_status;
 		}
 
@@ -656,363 +1351,624 @@ where
 		let file_hash = match get_file_hash(&gmod_file_path) {
 			Ok(file_hash) => file_hash,
 			Err(error) => {
-				terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 8: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 8: {error}").as_str() });
 				return new_integrity_status;
 			}
 		};
 
 		if file_hash != hashes["fixed"] {
-			terminal_write(writer, format!("\tFailed to Patch: {filename} | {integrity_status_string} / Step 9: Checksum mismatch").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+			emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Step 9: Checksum mismatch").as_str() });
+			return new_integrity_status;
+		}
+
+		if let Err(error) = journal.record_transition(filename, new_integrity_status, IntegrityStatus::Fixed) {
+			emit(writer, writer_is_interactive, Event::PatchFailed { filename, error: format!("{integrity_status_string} / Journal: {error}").as_str() });
 			return new_integrity_status;
 		}
 
-		terminal_write(writer, format!("\tPatched: {filename}").as_str(), true, None);
+		emit(writer, writer_is_interactive, Event::PatchFinish { filename });
 		new_integrity_status = IntegrityStatus::Fixed;
 	}
 
 	new_integrity_status
 }
 
-#[cfg(unix)]
-#[link(name = "c")]
-unsafe extern "C" {
-	safe fn geteuid() -> u32;
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum RestoreStatus {
+	NeedDelete = 0,
+	NeedBackup = 1,
+	NeedOriginal = 2,
+	Restored = 3
+}
+
+fn determine_file_restore_status(gmod_path: &Path, backup_dir: &Path, backup_index: &backup::BackupIndex, filename: &str, hashes: &IndexMap<String, String>) -> Result<RestoreStatus, String> {
+	let file_parts: Vec<&str> = filename.split("/").collect();
+	let file_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_path.to_path_buf(), &file_parts[..]), false);
+	let mut file_hash = BLANK_FILE_HASH.to_string();
+
+	if let Ok(file_path) = file_path {
+		file_hash = get_file_hash(&file_path)?;
+	}
+
+	if file_hash == hashes["original"] {
+		Ok(RestoreStatus::Restored)
+	} else if hashes["original"] == BLANK_FILE_HASH {
+		// The original file didn't exist, so get rid of it
+		Ok(RestoreStatus::NeedDelete)
+	} else if backup::has_original_backup(backup_dir, backup_index, filename, &hashes["original"]) {
+		// We backed up the original before patching it (and it's still intact), so use that
+		Ok(RestoreStatus::NeedBackup)
+	} else {
+		// No usable backup, so we need to download the original
+		Ok(RestoreStatus::NeedOriginal)
+	}
+}
+
+// Copy over the target file, creating its directory if it was deleted
+fn restore_file_from(source_path: &Path, target_path: &Path) -> io::Result<()> {
+	if let Some(target_path_dir) = target_path.parent() {
+		std::fs::create_dir_all(target_path_dir)?;
+	}
+
+	journal::copy_file_atomic(source_path, target_path)
 }
 
-async fn main_script_internal<W>(writer: fn() -> W, writer_is_interactive: bool, args: Args) -> Result<(), AlmightyError>
+#[allow(clippy::too_many_arguments)]
+fn restore_file<W>(
+	writer: fn() -> W,
+	writer_is_interactive: bool,
+	restore_status_strings: &HashMap<RestoreStatus, &str>,
+	gmod_path: &Path,
+	backup_dir: &Path,
+	cache_dir: Option<&Path>,
+	platform_masked: &str,
+	gmod_branch: &String,
+	filename: &&String,
+	restore_status: &RestoreStatus,
+	hashes: &&IndexMap<String, String>
+) -> RestoreStatus
 where
 	W: std::io::Write + 'static
 {
-	let now = Instant::now();
-	let sys = System::new_all();
+	emit(writer, writer_is_interactive, Event::RestoreStart { filename });
 
-	// Abort if another instance is already running
-	let pid_path = extend_pathbuf_and_return(std::env::current_exe().unwrap().parent().unwrap().to_path_buf(), &["gmodpatchtool.pid"]);
-	let running_instance_pid = tokio::fs::read_to_string(&pid_path).await;
-	if let Ok(pid) = running_instance_pid {
-		if let Ok(pid) = pid.parse::<usize>() {
-			if sys.process(sysinfo::Pid::from(pid)).is_some() {
-				return Err(AlmightyError::Generic(format!("Another instance of GModPatchTool is already running ({pid}).")));
+	let restore_status_string = restore_status_strings[restore_status];
+	let gmod_file_parts: Vec<&str> = filename.split("/").collect();
+	let gmod_file_path = extend_pathbuf_and_return(gmod_path.to_path_buf(), &gmod_file_parts[..]);
+
+	let restore_result = match restore_status {
+		RestoreStatus::NeedDelete => std::fs::remove_file(&gmod_file_path).map_err(|error| error.to_string()),
+		RestoreStatus::NeedBackup => {
+			let backup_file_path = backup::get_backup_file_path(backup_dir, filename);
+			restore_file_from(&backup_file_path, &gmod_file_path).map_err(|error| error.to_string())
+		},
+		RestoreStatus::NeedOriginal => {
+			let original_filename = format!("originals/{platform_masked}/{gmod_branch}/{filename}");
+			let original_file_parts: Vec<&str> = original_filename.split("/").collect();
+
+			match cache_dir {
+				Some(cache_dir) => {
+					let original_cache_file_path = extend_pathbuf_and_return(cache_dir.to_path_buf(), &original_file_parts[..]);
+					restore_file_from(&original_cache_file_path, &gmod_file_path).map_err(|error| error.to_string())
+				},
+				None => Err("No cache directory".to_string())
 			}
-		}
+		},
+		RestoreStatus::Restored => Ok(())
+	};
+
+	if let Err(error) = restore_result {
+		emit(writer, writer_is_interactive, Event::RestoreFailed { filename, error: format!("{restore_status_string}: {error}").as_str() });
+		return *restore_status;
 	}
 
-	// Create PID lockfile
-	let pid_write_result = tokio::fs::write(&pid_path, std::process::id().to_string()).await;
-	if let Err(error) = pid_write_result {
-		return Err(AlmightyError::Generic(format!("Failed to create gmodpatchtool.pid: {error}")));
+	// Sanity check the final checksum
+	if *restore_status != RestoreStatus::NeedDelete {
+		match get_file_hash(&gmod_file_path) {
+			Ok(file_hash) => {
+				if file_hash != hashes["original"] {
+					emit(writer, writer_is_interactive, Event::RestoreFailed { filename, error: format!("{restore_status_string}: Checksum mismatch").as_str() });
+					return *restore_status;
+				}
+			},
+			Err(error) => {
+				emit(writer, writer_is_interactive, Event::RestoreFailed { filename, error: format!("{restore_status_string}: {error}").as_str() });
+				return *restore_status;
+			}
+		}
 	}
 
-	// Get local version
-	let local_version: u32 = env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap();
+	emit(writer, writer_is_interactive, Event::RestoreFinish { filename });
 
-	// Get remote version
-	terminal_write(writer, "Getting remote version...", true, None);
+	RestoreStatus::Restored
+}
 
-	let remote_version_response = get_http_response(writer, writer_is_interactive, &TEXT_SERVER_ROOTS, "version.txt").await;
+#[allow(clippy::too_many_arguments)]
+async fn restore_files<W>(
+	writer: fn() -> W,
+	writer_is_interactive: bool,
+	downloader: &Arc<download::Downloader>,
+	mirrors: &MirrorList,
+	offline_bundle: Option<&bundle::OfflineBundle>,
+	gmod_path: &Path,
+	platform_masked: &str,
+	gmod_branch: &String,
+	platform_branch_files: &IndexMap<String, IndexMap<String, String>>,
+	disable_cache: bool,
+	dry_run: bool,
+	now: Instant
+) -> Result<RunOutcome, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	terminal_write(writer, "Determining file restore status...", true, None, MessageLevel::Info);
 
-	if remote_version_response.is_none() {
-		return Err(AlmightyError::Generic("Couldn't get remote version. Please check your internet connection!".to_string()));
-	}
+	let restore_status_strings = HashMap::from([
+		(RestoreStatus::NeedDelete, "Needs Delete"),
+		(RestoreStatus::NeedBackup, "Needs Restore from Backup"),
+		(RestoreStatus::NeedOriginal, "Needs Original"),
+		(RestoreStatus::Restored, "Already Original")
+	]);
 
-	let remote_version_response = remote_version_response.unwrap();
-	let remote_version: u32 = remote_version_response.text()
-	.await?
-	.trim()
-	.parse()?;
+	let backup_dir = backup::get_backup_dir(gmod_path);
+	let backup_index = backup::read_backup_index(&backup_dir, gmod_path);
 
-	if local_version >= remote_version {
-		terminal_write(writer, format!("You are running the latest version of GModPatchTool [Local: {local_version} / Remote: {remote_version}]!\n").as_str(), true, if writer_is_interactive { Some("green") } else { None });
-	} else {
-		terminal_write(writer, "WARNING: GModPatchTool is out of date! Please get the latest version at\nhttps://github.com/solsticegamestudios/GModPatchTool/releases", true, if writer_is_interactive { Some("red") } else { None });
+	#[allow(clippy::type_complexity)]
+	let restore_results: Vec<(&String, Result<RestoreStatus, String>, &IndexMap<String, String>)> = platform_branch_files.par_iter()
+	.map(|(filename, hashes)| {
+		let restore_result = determine_file_restore_status(gmod_path, &backup_dir, &backup_index, filename, hashes);
 
-		let mut secs_to_continue: u8 = 5;
-		while secs_to_continue > 0 {
-			terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None });
-			writer().flush().unwrap();
-			tokio::time::sleep(time::Duration::from_secs(1)).await;
-			secs_to_continue -= 1;
+		match &restore_result {
+			Ok(restore_status) => {
+				let restore_status_string = restore_status_strings[restore_status];
+				emit(writer, writer_is_interactive, Event::FileStatus { filename, status: format!("{restore_status:?}").as_str(), description: restore_status_string });
+			},
+			Err(error) => {
+				emit(writer, writer_is_interactive, Event::FileStatusFailed { filename, error });
+			}
 		}
 
-		// Clear continuing line
-		if writer_is_interactive {
-			terminal_write(writer, "\x1B[0K\n", false, None);
+		(filename, restore_result, hashes)
+	}).collect();
+
+	let mut pending_files: Vec<(&String, RestoreStatus, &IndexMap<String, String>)> = vec![];
+	for (filename, result, hashes) in restore_results {
+		match result {
+			Ok(result) => {
+				if result != RestoreStatus::Restored {
+					pending_files.push((filename, result, hashes));
+				}
+			},
+			Err(_) => {
+				return Err(AlmightyError::Generic("Failed to get restore status of one or more files!".to_string()));
+			}
 		}
 	}
 
-	// Warn/Exit if running as root/admin
-	#[cfg(windows)]
-	let root = is_elevated();
+	let pending_files_len = pending_files.len();
 
-	#[cfg(unix)]
-	let root = geteuid() == 0;
+	if dry_run {
+		terminal_write(writer, "\nRestore Plan (--dry-run, nothing will be changed):", true, None, MessageLevel::Info);
 
-	if root {
-		if args.run_as_root_with_security_risk {
-			terminal_write(writer, "WARNING: You are running GModPatchTool as root/with admin privileges. This may cause issues and is not typically necessary.", true, if writer_is_interactive { Some("red") } else { None });
+		let mut originals_plan = DownloadPlan::default();
+		let cache_dir = get_dry_run_cache_dir(disable_cache);
+		for restore_status in [RestoreStatus::NeedDelete, RestoreStatus::NeedBackup, RestoreStatus::NeedOriginal] {
+			let restore_status_files: Vec<_> = pending_files.iter().filter(|(_, pending_restore_status, _)| *pending_restore_status == restore_status).collect();
 
-			let mut secs_to_continue: u8 = 10;
-			while secs_to_continue > 0 {
-				terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None });
-				writer().flush().unwrap();
-				tokio::time::sleep(time::Duration::from_secs(1)).await;
-				secs_to_continue -= 1;
+			if !restore_status_files.is_empty() {
+				let restore_status_string = restore_status_strings[&restore_status];
+				terminal_write(writer, format!("\t{restore_status_string}: {} file(s)", restore_status_files.len()).as_str(), true, None, MessageLevel::Info);
 			}
 
-			// Clear continuing line
-			if writer_is_interactive {
-				terminal_write(writer, "\x1B[0K\n", false, None);
+			if restore_status == RestoreStatus::NeedOriginal {
+				for (filename, _, hashes) in restore_status_files {
+					originals_plan.add(cache_dir.as_deref(), format!("originals/{platform_masked}/{gmod_branch}/{filename}").as_str(), &hashes["original"], get_manifest_size(hashes, "original_size"));
+				}
 			}
-		} else {
-			let elevated_msg = format!("You are running GModPatchTool as root/with admin privileges{}. This may cause issues and is not typically necessary.\n\nIF YOU KNOW WHAT YOU'RE DOING, you can allow this by running the tool with --run-as-root-with-security-risk. Aborting...", if cfg!(windows) { " (is User Account Control turned off?)" } else { "" });
-			return Err(AlmightyError::Generic(elevated_msg));
 		}
-	}
-
-	// Abort if GMod is currently running
-	if sys.processes_by_exact_name("gmod.exe".as_ref()).next().is_some() || sys.processes_by_exact_name("gmod".as_ref()).next().is_some() {
-		return Err(AlmightyError::Generic("Garry's Mod is currently running. Please close it before running this tool.".to_string()));
-	}
 
-	// Warning for macOS users
-	#[cfg(target_os = "macos")]
-	{
-		terminal_write(writer, "WARNING: Garry's Mod is not well supported on macOS and may not be supported at all in the future:", true, if writer_is_interactive { Some("red") } else { None });
-		terminal_write(writer, "\thttps://winteris.moe/share/2025-08-07_20-53-45.png", true, None);
-		terminal_write(writer, "\nOptions for playing the Windows version of GMod on macOS are located here:", true, if writer_is_interactive { Some("red") } else { None });
-		terminal_write(writer, "\thttps://github.com/solsticegamestudios/GModPatchTool/issues/170", true, None);
+		terminal_write(writer, format!("\tDownload originals/: {}", originals_plan.describe()).as_str(), true, None, MessageLevel::Info);
 
-		let mut secs_to_continue: u8 = 10;
-		while secs_to_continue > 0 {
-			terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None });
-			writer().flush().unwrap();
-			tokio::time::sleep(time::Duration::from_secs(1)).await;
-			secs_to_continue -= 1;
+		if pending_files_len > 0 {
+			terminal_write(writer, "\nRestoring is needed. Run the tool without --dry-run to apply it.", true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			return Ok(RunOutcome::ChangesNeeded);
 		}
 
-		// Clear continuing line
-		if writer_is_interactive {
-			terminal_write(writer, "\x1B[0K\n", false, None);
-		}
+		terminal_write(writer, "\nNo files need restoring!", true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
+		return Ok(RunOutcome::Done);
 	}
 
-	// Find Steam
-	let mut steam_path = None;
-	if let Some(steam_path_arg) = args.steam_path {
-		// Make sure the path the user is forcing actually exists
-		let steam_path_arg_pathbuf = pathbuf_to_canonical_pathbuf(steam_path_arg.clone(), true);
-
-		steam_path = match steam_path_arg_pathbuf {
-			Ok(steam_path) => Some(steam_path),
-			Err(error) => {
-				return Err(AlmightyError::Generic(format!("Please check the --steam_path argument is pointing to a valid path:\n\t{error}")));
+	if pending_files_len > 0 {
+		// Only bother with the cache if we don't have backups for everything
+		let mut cache_dir = None;
+		if pending_files.iter().any(|(_, restore_status, _)| *restore_status == RestoreStatus::NeedOriginal) {
+			let new_cache_dir = get_cache_dir(writer, writer_is_interactive, disable_cache).await?;
+
+			terminal_write(writer, "Downloading original files...", true, None, MessageLevel::Info);
+
+			// Filename on the mirrors, hash, and size
+			let downloads: Vec<(String, String, Option<u64>)> = pending_files.iter()
+				.filter(|(_, restore_status, _)| *restore_status == RestoreStatus::NeedOriginal)
+				.map(|(filename, _, hashes)| (format!("originals/{platform_masked}/{gmod_branch}/{filename}.zst"), hashes["original"].clone(), get_manifest_size(hashes, "original_size")))
+				.collect();
+
+			extract_from_offline_bundle(offline_bundle, &downloads)?;
+
+			let total_progress = Arc::new(TotalProgress::new(0));
+			let mut download_futures = JoinSet::new();
+			for (filename, hash, size) in downloads {
+				total_progress.add_expected(size.unwrap_or_default());
+				download_futures.spawn(download_file_to_cache(writer, writer_is_interactive, downloader.clone(), mirrors.binary.clone(), new_cache_dir.clone(), filename, hash, size, total_progress.clone()));
 			}
-		}
-	} else {
-		// Windows
-		#[cfg(windows)]
-		{
-			if let Ok(steam_reg_key) = windows_registry::CURRENT_USER.open("Software\\Valve\\Steam") {
-				if let Ok(steam_reg_path) = steam_reg_key.get_string("SteamPath") {
-					steam_path = string_to_canonical_pathbuf(steam_reg_path);
+
+			while let Some(download_result) = download_futures.join_next().await {
+				if !matches!(download_result, Ok(Ok(()))) {
+					return Err(AlmightyError::Generic("Failed to download one or more original files!".to_string()));
 				}
 			}
-		}
 
-		// macOS
-		#[cfg(target_os = "macos")]
-		{
-			// $HOME/Library/Application Support/Steam
-			let mut steam_data_path = dirs::data_dir().unwrap();
-			steam_data_path.push("Steam");
-			steam_path = pathbuf_to_canonical_pathbuf(steam_data_path, true).ok();
+			cache_dir = Some(new_cache_dir);
 		}
 
-		// Anything else (we assume Linux)
-		#[cfg(not(any(windows, target_os = "macos")))]
-		{
-			let home_dir = dirs::home_dir().unwrap();
-			let possible_steam_paths = vec![
-				// Snap
-				extend_pathbuf_and_return(home_dir.clone(), &["snap", "steam", "common", ".local", "share", "Steam"]),
-				extend_pathbuf_and_return(home_dir.clone(), &["snap", "steam", "common", ".steam", "steam"]),
-				// Flatpak
-				extend_pathbuf_and_return(home_dir.clone(), &[".var", "app", "com.valvesoftware.Steam", ".local", "share", "Steam"]),
-				extend_pathbuf_and_return(home_dir.clone(), &[".var", "app", "com.valvesoftware.Steam", ".steam", "steam"]),
-				// Home
-				extend_pathbuf_and_return(home_dir.clone(), &[".steam", "steam"]),
-				//extend_pathbuf_and_return(home_dir.clone(), &[".steam"]),
-			];
-			let mut valid_steam_paths = vec![];
-
-			for pathbuf in possible_steam_paths {
-				if let Ok(pathbuf) = pathbuf_to_canonical_pathbuf(pathbuf, true) {
-					if !valid_steam_paths.contains(&pathbuf) {
-						valid_steam_paths.push(pathbuf);
-					}
-				}
-			}
+		terminal_write(writer, format!("\nRestoring {pending_files_len} file(s)...").as_str(), true, None, MessageLevel::Info);
 
-			// $XDG_DATA_HOME/Steam
-			if let Some(steam_xdg_path) = dirs::data_dir() {
-				if let Ok(steam_xdg_pathbuf) = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(steam_xdg_path, &["Steam"]), true) {
-					if !valid_steam_paths.contains(&steam_xdg_pathbuf) {
-						valid_steam_paths.push(steam_xdg_pathbuf);
-					}
-				}
-			}
+		let restore_results: Vec<(&String, RestoreStatus)> = pending_files.par_iter()
+		.map(|(filename, restore_status, hashes)| {
+			let new_restore_status = restore_file(
+				writer,
+				writer_is_interactive,
+				&restore_status_strings,
+				gmod_path,
+				&backup_dir,
+				cache_dir.as_deref(),
+				platform_masked,
+				gmod_branch,
+				filename,
+				restore_status,
+				hashes
+			);
 
-			// Set the Steam path if at least one is valid
-			// Warn if there's more than one
-			if !valid_steam_paths.is_empty() {
-				if valid_steam_paths.len() > 1 {
-					let mut valid_steam_paths_str: String = "".to_string();
-					for pathbuf in &valid_steam_paths {
-						valid_steam_paths_str += "\n\t- ";
-						valid_steam_paths_str += &pathbuf.to_string_lossy();
-					}
+			(*filename, new_restore_status)
+		}).collect();
 
-					terminal_write(writer, format!("Warning: Multiple Steam Installations Detected! This may cause issues:{valid_steam_paths_str}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
+		for (_, restore_status) in restore_results {
+			if restore_status != RestoreStatus::Restored {
+				return Err(AlmightyError::Generic("Failed to restore one or more files!".to_string()));
+			}
+		}
 
-					let mut secs_to_continue: u8 = 5;
-					while secs_to_continue > 0 {
-						terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None });
-						writer().flush().unwrap();
-						tokio::time::sleep(time::Duration::from_secs(1)).await;
-						secs_to_continue -= 1;
-					}
+		if disable_cache && let Some(cache_dir) = cache_dir {
+			let remove_result = tokio::fs::remove_dir_all(cache_dir).await;
 
-					// Clear continuing line
-					if writer_is_interactive {
-						terminal_write(writer, "\x1B[0K\n", false, None);
-					}
+			match remove_result {
+				Ok(_) => {
+					terminal_write(writer,"\n[disable-cache:Post] Successfully cleared GModPatchTool cache directory.", true, None, MessageLevel::Info);
+				},
+				Err(error) => {
+					terminal_write(writer, format!("\n[disable-cache:Post] Failed to clear GModPatchTool cache directory: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
 				}
-
-				steam_path = Some(valid_steam_paths[0].clone());
 			}
 		}
+	} else {
+		terminal_write(writer, "No files need restoring!", true, None, MessageLevel::Info);
 	}
 
-	if steam_path.is_none() {
-		return Err(AlmightyError::Generic("Couldn't find Steam. If it's installed, try using the --steam_path argument to force a specific path.".to_string()));
+	// Restoring supersedes whatever an interrupted patch run left behind
+	if let Err(error) = journal::clear_journal(&backup_dir) {
+		terminal_write(writer, format!("Failed to clear patch journal: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
 	}
 
-	let steam_path = steam_path.unwrap();
-	let steam_path_str = steam_path.to_string_lossy();
+	let now = now.elapsed().as_secs_f64();
+	terminal_write(writer, format!("\nGarry's Mod restored to unpatched files successfully! Took {now} second(s).").as_str(), true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
+
+	Ok(RunOutcome::Done)
+}
 
-	terminal_write(writer, format!("Steam Path: {steam_path_str}\n").as_str(), true, None);
+// Prints everything a patch run would do, for --dry-run
+#[allow(clippy::too_many_arguments)]
+fn print_patch_plan<W>(
+	writer: fn() -> W,
+	writer_is_interactive: bool,
+	integrity_status_strings: &HashMap<IntegrityStatus, &str>,
+	pending_files: &[(&String, IntegrityStatus, &IndexMap<String, String>)],
+	platform_branch_files: &IndexMap<String, IndexMap<String, String>>,
+	gmod_path: &Path,
+	platform_masked: &str,
+	gmod_branch: &String,
+	disable_cache: bool
+) -> RunOutcome
+where
+	W: std::io::Write + 'static
+{
+	terminal_write(writer, "\nPatch Plan (--dry-run, nothing will be changed):", true, None, MessageLevel::Info);
 
-	// Get most recent Steam User, which is probably the one they're using/want
-	let steam_loginusers_path = extend_pathbuf_and_return(steam_path.clone(), &["config", "loginusers.vdf"]);
-	let steam_loginusers_str = tokio::fs::read_to_string(steam_loginusers_path).await;
+	for integrity_status in [IntegrityStatus::NeedDelete, IntegrityStatus::NeedOriginal, IntegrityStatus::NeedWipeFix, IntegrityStatus::NeedFix] {
+		let integrity_status_count = pending_files.iter().filter(|(_, pending_integrity_status, _)| *pending_integrity_status == integrity_status).count();
 
-	if steam_loginusers_str.is_err() {
-		return Err(AlmightyError::Generic("Couldn't find Steam loginusers.vdf. Have you ever launched/signed in to Steam?".to_string()));
+		if integrity_status_count > 0 {
+			let integrity_status_string = integrity_status_strings[&integrity_status];
+			terminal_write(writer, format!("\t{integrity_status_string}: {integrity_status_count} file(s)").as_str(), true, None, MessageLevel::Info);
+		}
 	}
 
-	let steam_loginusers_str = steam_loginusers_str.unwrap();
-	let steam_loginusers = vdf::from_str(steam_loginusers_str.as_str());
+	// Same logic as the download step
+	let cache_dir = get_dry_run_cache_dir(disable_cache);
+	let mut originals_plan = DownloadPlan::default();
+	let mut patches_plan = DownloadPlan::default();
+	for (filename, integrity_status, hashes) in pending_files {
+		if *integrity_status == IntegrityStatus::NeedOriginal {
+			originals_plan.add(cache_dir.as_deref(), format!("originals/{platform_masked}/{gmod_branch}/{filename}").as_str(), &hashes["original"], get_manifest_size(hashes, "original_size"));
+		}
 
-	if let Err(error) = steam_loginusers {
-		return Err(AlmightyError::Generic(format!("Couldn't parse Steam loginusers.vdf. Is the file corrupt?\n\t{error}")));
+		if *integrity_status != IntegrityStatus::NeedDelete {
+			patches_plan.add(cache_dir.as_deref(), format!("patches/{platform_masked}/{gmod_branch}/{filename}.bsdiff").as_str(), &hashes["patch"], get_manifest_size(hashes, "patch_size"));
+		}
 	}
 
-	let mut steam_user: HashMap<&str, String> = HashMap::new();
-	let steam_loginusers: HashMap<&str, SteamUser> = steam_loginusers.unwrap();
-	for (other_steam_id_64, other_steam_user) in steam_loginusers {
-		let mostrecent = other_steam_user.most_recent;
-		let timestamp = other_steam_user.timestamp;
+	terminal_write(writer, format!("\tDownload originals/: {}", originals_plan.describe()).as_str(), true, None, MessageLevel::Info);
+	terminal_write(writer, format!("\tDownload patches/: {}", patches_plan.describe()).as_str(), true, None, MessageLevel::Info);
+
+	// Executables missing their executable bit get fixed even if they don't need patching
+	#[cfg_attr(not(unix), expect(unused_mut, reason = "used on unix"))]
+	let mut permissions_count = 0;
+
+	#[cfg(unix)]
+	{
+		for (filename, fileinfo) in platform_branch_files {
+			if fileinfo.get("executable").is_some_and(|executable| executable == "true") {
+				let gmod_file_parts: Vec<&str> = filename.split("/").collect();
+				let gmod_file_path = extend_pathbuf_and_return(gmod_path.to_path_buf(), &gmod_file_parts[..]);
+
+				if let Ok(metadata) = std::fs::metadata(gmod_file_path) && metadata.permissions().mode() & 0o111 != 0o111 {
+					permissions_count += 1;
+				}
+			}
+		}
 
-		if !steam_user.contains_key("Timestamp") || mostrecent || (timestamp > steam_user.get("Timestamp").unwrap().parse::<u64>().unwrap()) {
-			steam_user.insert("SteamID64", other_steam_id_64.to_string());
-			steam_user.insert("Timestamp", timestamp.to_string());
-			steam_user.insert("AccountName", other_steam_user.account_name);
-			steam_user.insert("PersonaName", other_steam_user.persona_name);
+		if permissions_count > 0 {
+			terminal_write(writer, format!("\tNeeds Permissions: {permissions_count} file(s)").as_str(), true, None, MessageLevel::Info);
 		}
 	}
 
-	if !steam_user.contains_key("Timestamp") {
-		return Err(AlmightyError::Generic("Couldn't find Steam User. Have you ever launched/signed in to Steam?".to_string()));
+	#[cfg(not(unix))]
+	let _ = (platform_branch_files, gmod_path);
+
+	if !pending_files.is_empty() || permissions_count > 0 {
+		terminal_write(writer, "\nPatching is needed. Run the tool without --dry-run to apply it.", true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+		RunOutcome::ChangesNeeded
+	} else {
+		terminal_write(writer, "\nNo files need patching!", true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
+		RunOutcome::Done
+	}
+}
+
+// Puts back every file an interrupted patch run touched, using the backups it journaled
+fn rollback_interrupted_run<W>(writer: fn() -> W, writer_is_interactive: bool, gmod_path: &Path, backup_dir: &Path, journal_entries: &[journal::JournalEntry]) -> Result<(), AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let mut backup_hashes: HashMap<&str, &str> = HashMap::new();
+	let mut not_backed_up_files: Vec<&str> = vec![];
+	let mut touched_files: Vec<&str> = vec![];
+	for journal_entry in journal_entries {
+		match journal_entry {
+			journal::JournalEntry::Backup { filename, hash } => {
+				backup_hashes.insert(filename, hash);
+			},
+			journal::JournalEntry::NotBackedUp { filename } => {
+				not_backed_up_files.push(filename);
+			},
+			journal::JournalEntry::Start { filename, .. } if !touched_files.contains(&filename.as_str()) => {
+				touched_files.push(filename);
+			},
+			_ => {}
+		}
 	}
 
-	let steam_id = SteamId::new(steam_user.get("SteamID64").unwrap().parse::<u64>().unwrap()).unwrap();
+	terminal_write(writer, format!("\nRolling back {} file(s)...", touched_files.len()).as_str(), true, None, MessageLevel::Info);
+
+	let mut failed = false;
+	for filename in touched_files {
+		let gmod_file_parts: Vec<&str> = filename.split("/").collect();
+		let gmod_file_path = extend_pathbuf_and_return(gmod_path.to_path_buf(), &gmod_file_parts[..]);
 
-	terminal_write(writer, format!("Steam User: {} ({} / {})\n", steam_user.get("PersonaName").unwrap(), steam_user.get("SteamID64").unwrap(), steam_id.steam3id()).as_str(), true, None);
+		// Whatever is there now is as close as we can get; removing it would be worse
+		if !backup_hashes.contains_key(filename) && not_backed_up_files.contains(&filename) {
+			terminal_write(writer, format!("\tLeft As Is: {filename} | It wasn't the original before the run, so there's no copy of how it was. To go back to the unpatched files instead, run the tool with restore.").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			continue;
+		}
 
-	// Get Steam Libraries
-	let mut steam_libraryfolders_path = extend_pathbuf_and_return(steam_path.clone(), &["config", "libraryfolders.vdf"]);
-	let mut steam_libraryfolders_str = tokio::fs::read_to_string(steam_libraryfolders_path).await;
+		let rollback_result = match backup_hashes.get(filename) {
+			Some(backup_hash) => {
+				let backup_file_path = backup::get_backup_file_path(backup_dir, filename);
 
-	// Try steamapps
-	if steam_libraryfolders_str.is_err() {
-		steam_libraryfolders_path = extend_pathbuf_and_return(steam_path.clone(), &["steamapps", "libraryfolders.vdf"]);
-		steam_libraryfolders_str = tokio::fs::read_to_string(steam_libraryfolders_path).await;
-	}
+				match get_file_hash(&backup_file_path) {
+					Ok(file_hash) if file_hash == *backup_hash => restore_file_from(&backup_file_path, &gmod_file_path).map_err(|error| error.to_string()),
+					Ok(_) => Err("Backup checksum mismatch".to_string()),
+					Err(error) => Err(error)
+				}
+			},
+			// Nothing was backed up, so the file didn't exist before the run
+			None => match std::fs::remove_file(&gmod_file_path) {
+				Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.to_string()),
+				_ => Ok(())
+			}
+		};
 
-	// Try SteamApps with capitalization
-	if steam_libraryfolders_str.is_err() {
-		steam_libraryfolders_path = extend_pathbuf_and_return(steam_path.clone(), &["SteamApps", "libraryfolders.vdf"]);
-		steam_libraryfolders_str = tokio::fs::read_to_string(steam_libraryfolders_path).await;
+		match rollback_result {
+			Ok(_) => {
+				terminal_write(writer, format!("\tRolled Back: {filename}").as_str(), true, None, MessageLevel::Info);
+			},
+			Err(error) => {
+				terminal_write(writer, format!("\tFailed to Roll Back: {filename} | {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+				failed = true;
+			}
+		}
 	}
 
-	if steam_libraryfolders_str.is_err() {
-		return Err(AlmightyError::Generic("Couldn't find Steam libraryfolders.vdf. Have you ever launched/signed in to Steam?".to_string()));
+	if failed {
+		return Err(AlmightyError::Generic("Failed to roll back one or more files! Try running the tool with `restore`, or verify Garry's Mod's files in Steam.".to_string()));
 	}
 
-	let steam_libraryfolders_str = steam_libraryfolders_str.unwrap();
-	let steam_libraryfolders = vdf::from_str(steam_libraryfolders_str.as_str());
+	Ok(())
+}
+
+#[cfg(unix)]
+#[link(name = "c")]
+unsafe extern "C" {
+	safe fn geteuid() -> u32;
+}
 
-	if let Err(error) = steam_libraryfolders {
-		return Err(AlmightyError::Generic(format!("Couldn't parse Steam libraryfolders.vdf. Is the file corrupt?\n\t{error}")));
+// The install GMod is actually in, since there may be old/empty Steam installs lying around
+// Otherwise, the first one we found
+fn get_default_steam_installation_id(steam_installations: &[SteamInstallation]) -> Option<usize> {
+	if steam_installations.is_empty() {
+		return None;
 	}
 
-	// Get GMod Steam Library and Manifest
-	let mut gmod_steam_library_path = None;
-	let mut gmod_manifest_str = None;
+	Some(steam_installations.iter().position(SteamInstallation::has_gmod).unwrap_or(0))
+}
 
-	let steam_libraryfolders: HashMap<&str, SteamLibraryFolder> = steam_libraryfolders.unwrap();
-	for (_, steam_library) in steam_libraryfolders {
-		// Get potential Steam Library
-		let new_gmod_steam_library_path = string_to_canonical_pathbuf(steam_library.path);
+// --gmod-install picks from every GMod install in the Steam libraries
+// By default, the one Steam's libraryfolders.vdf says it's in, since the others are probably leftovers
+fn select_gmod_installs<W>(writer: fn() -> W, writer_is_interactive: bool, gmod_installs: Vec<steam::AppInstall>, gmod_install_arg: &str) -> Result<Vec<steam::AppInstall>, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let gmod_install_arg = gmod_install_arg.trim();
+	let gmod_install_count = gmod_installs.len();
+	let listed_gmod_install_count = gmod_installs.iter().filter(|gmod_install| gmod_install.listed_by_steam).count();
+
+	let selected_ids: Vec<usize> = if gmod_install_arg.eq_ignore_ascii_case("active") {
+		vec![gmod_installs.iter().position(|gmod_install| gmod_install.listed_by_steam).unwrap_or(0)]
+	} else if gmod_install_arg.eq_ignore_ascii_case("all") {
+		(0..gmod_install_count).collect()
+	} else if let Ok(gmod_install_number) = gmod_install_arg.parse::<usize>() {
+		if gmod_install_number == 0 || gmod_install_number > gmod_install_count {
+			return Err(AlmightyError::Generic(format!("There's no Garry's Mod install #{gmod_install_number} (found {gmod_install_count}).")));
+		}
 
-		if let Some(new_gmod_steam_library_path) = new_gmod_steam_library_path {
-			// Get GMod manifest
-			let mut new_gmod_manifest_path = extend_pathbuf_and_return(new_gmod_steam_library_path.to_path_buf(), &["steamapps", "appmanifest_4000.acf"]);
-			let mut new_gmod_manifest_str = tokio::fs::read_to_string(new_gmod_manifest_path).await;
+		vec![gmod_install_number - 1]
+	} else {
+		// Either the library or the GarrysMod directory in it
+		let gmod_install_arg_pathbuf = pathbuf_to_canonical_pathbuf(PathBuf::from(gmod_install_arg), true);
 
-			// Try SteamApps with capitalization
-			if new_gmod_manifest_str.is_err() {
-				new_gmod_manifest_path = extend_pathbuf_and_return(new_gmod_steam_library_path.to_path_buf(), &["SteamApps", "appmanifest_4000.acf"]);
-				new_gmod_manifest_str = tokio::fs::read_to_string(new_gmod_manifest_path).await;
-			}
+		if let Err(error) = gmod_install_arg_pathbuf {
+			return Err(AlmightyError::Generic(format!("Please check the --gmod-install argument is active, all, a number, or a valid path:\n\t{error}")));
+		}
 
-			if new_gmod_manifest_str.is_ok() {
-				gmod_steam_library_path = Some(new_gmod_steam_library_path);
-				gmod_manifest_str = new_gmod_manifest_str.ok();
-				break;
-			}
+		let gmod_install_arg_pathbuf = gmod_install_arg_pathbuf.unwrap();
+		let gmod_install_id = gmod_installs.iter().position(|gmod_install| gmod_install.library_path == gmod_install_arg_pathbuf || gmod_install.find_install_dir().as_ref() == Some(&gmod_install_arg_pathbuf));
+
+		if gmod_install_id.is_none() {
+			return Err(AlmightyError::Generic(format!("Couldn't find a Garry's Mod install in {}.", gmod_install_arg_pathbuf.display())));
+		}
+
+		vec![gmod_install_id.unwrap()]
+	};
+
+	// Report all of them if there's a choice to make
+	// Only a warning if we can't tell which one Steam uses
+	if gmod_install_count > 1 {
+		let ambiguous = listed_gmod_install_count != 1;
+
+		terminal_write(writer, if ambiguous { "Warning: Multiple Garry's Mod Installs Detected! Steam doesn't list exactly one of them as installed:" } else { "Multiple Garry's Mod Installs Detected!" }, true, if writer_is_interactive && ambiguous { Some("yellow") } else { None }, if ambiguous { MessageLevel::Warning } else { MessageLevel::Info });
+
+		for (gmod_install_id, gmod_install) in gmod_installs.iter().enumerate() {
+			emit(writer, writer_is_interactive, Event::GmodInstall {
+				number: gmod_install_id + 1,
+				library: &gmod_install.library_path.to_string_lossy(),
+				build_id: gmod_install.manifest.build_id,
+				state_flags: gmod_install.manifest.state_flags,
+				last_updated: gmod_install.manifest.last_updated,
+				listed_by_steam: gmod_install.listed_by_steam,
+				selected: selected_ids.contains(&gmod_install_id)
+			});
 		}
+
+		terminal_write(writer, "\tTo patch a different one, run the tool with --gmod-install <number, path, or all>.\n", true, None, MessageLevel::Info);
+	} else if listed_gmod_install_count == 0 {
+		terminal_write(writer, "Warning: Steam's libraryfolders.vdf doesn't list Garry's Mod as installed in this library. It may be a leftover copy.\n", true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+	}
+
+	Ok(gmod_installs.into_iter().enumerate().filter(|(gmod_install_id, _)| selected_ids.contains(gmod_install_id)).map(|(_, gmod_install)| gmod_install).collect())
+}
+
+// e.g. "3 day(s) ago", for Steam's Unix timestamps
+fn describe_timestamp_age(timestamp: u64) -> String {
+	if timestamp == 0 {
+		return "Never".to_string();
+	}
+
+	let now = time::SystemTime::now().duration_since(time::UNIX_EPOCH).unwrap_or_default().as_secs();
+	let days = now.saturating_sub(timestamp) / 86400;
+
+	if days == 0 { "Today".to_string() } else { format!("{days} day(s) ago") }
+}
+
+// --steam-install is either a number from list-steam or a path
+fn get_steam_installation_arg(steam_installations: Vec<SteamInstallation>, steam_install_arg: &str) -> Result<SteamInstallation, AlmightyError> {
+	if let Ok(steam_installation_number) = steam_install_arg.trim().parse::<usize>() {
+		let steam_installation_count = steam_installations.len();
+
+		return steam_installation_number.checked_sub(1)
+			.and_then(|steam_installation_id| steam_installations.into_iter().nth(steam_installation_id))
+			.ok_or_else(|| AlmightyError::Generic(format!("There's no Steam install #{steam_installation_number} (found {steam_installation_count}). Run the tool with list-steam to see them.")));
+	}
+
+	// Make sure the path the user is forcing actually exists
+	match pathbuf_to_canonical_pathbuf(PathBuf::from(steam_install_arg), true) {
+		Ok(steam_path) => Ok(SteamInstallation::new(steam_path)),
+		Err(error) => Err(AlmightyError::Generic(format!("Please check the --steam-install argument is pointing to a valid path:\n\t{error}")))
 	}
+}
+
+fn list_steam_installations<W>(writer: fn() -> W, writer_is_interactive: bool) -> Result<RunOutcome, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let steam_installations = steam::find_installations();
 
-	//gmod_steam_library_path.is_none() ||
-	if gmod_manifest_str.is_none() {
-		return Err(AlmightyError::Generic("Couldn't find GMod's appmanifest_4000.acf. Is Garry's Mod installed?".to_string()));
+	if steam_installations.is_empty() {
+		return Err(AlmightyError::Generic("Couldn't find Steam. If it's installed, try using the --steam-install argument to force a specific path.".to_string()));
 	}
 
-	let gmod_manifest_str = gmod_manifest_str.unwrap();
-	let gmod_manifest = vdf::from_str(gmod_manifest_str.as_str());
+	let default_steam_installation_id = get_default_steam_installation_id(&steam_installations);
 
-	if let Err(error) = gmod_manifest {
-		return Err(AlmightyError::Generic(format!("Couldn't parse GMod's appmanifest_4000.acf. Is the file corrupt?\n\t{error}")));
+	for (steam_installation_id, steam_installation) in steam_installations.iter().enumerate() {
+		// Missing/broken files just show up as nothing; the patch run reports them properly
+		let users: Vec<ListedSteamUser> = steam_installation.read_users().unwrap_or_default().into_iter().map(|steam_user| ListedSteamUser {
+			steam_id_64: steam_user.steam_id_64,
+			account_name: steam_user.account_name,
+			persona_name: steam_user.persona_name,
+			most_recent: steam_user.most_recent
+		}).collect();
+
+		let libraries: Vec<ListedSteamLibrary> = steam_installation.read_libraries().unwrap_or_default().into_iter().map(|steam_library| ListedSteamLibrary {
+			gmod_installed: steam_library.read_app_manifest(steam::GMOD_APP_ID).is_some(),
+			path: steam_library.path.to_string_lossy().to_string()
+		}).collect();
+
+		emit(writer, writer_is_interactive, Event::SteamInstallation {
+			number: steam_installation_id + 1,
+			path: &steam_installation.path.to_string_lossy(),
+			default: Some(steam_installation_id) == default_steam_installation_id,
+			gmod_installed: libraries.iter().any(|library| library.gmod_installed),
+			users,
+			libraries
+		});
 	}
 
-	let gmod_steam_library_path = gmod_steam_library_path.unwrap();
-	let gmod_steam_library_path_str = gmod_steam_library_path.to_string_lossy();
+	terminal_write(writer, "To use a specific one, run the tool with --steam-install <number or path>.", true, None, MessageLevel::Info);
 
-	terminal_write(writer, format!("GMod Steam Library: {gmod_steam_library_path_str}\n").as_str(), true, None);
+	Ok(RunOutcome::Done)
+}
 
+fn check_gmod_app_state<W>(writer: fn() -> W, writer_is_interactive: bool, gmod_manifest: &steam::SteamAppManifest) -> Result<(), AlmightyError>
+where
+	W: std::io::Write + 'static
+{
 	// Get GMod app state
-	let gmod_manifest: SteamAppManifest = gmod_manifest.unwrap();
 	let gmod_stateflags = gmod_manifest.state_flags;
 	//let gmod_downloadtype = gmod_manifest.download_type; // TODO: Figure this out...
 	let gmod_scheduledautoupdate = gmod_manifest.scheduled_auto_update;
@@ -1022,38 +1978,118 @@ where
 	let gmod_bytesstaged = gmod_manifest.bytes_staged;
 	let gmod_bytestostage = gmod_manifest.bytes_to_stage;
 
-	terminal_write(writer, format!("GMod App State: {gmod_stateflags} | {gmod_scheduledautoupdate} | {gmod_fullvalidatebeforenextupdate} | {gmod_bytesdownloaded}/{gmod_bytestodownload} | {gmod_bytesstaged}/{gmod_bytestostage} \n").as_str(), true, None);
+	emit(writer, writer_is_interactive, Event::GmodAppState {
+		state_flags: gmod_stateflags,
+		scheduled_auto_update: gmod_scheduledautoupdate,
+		full_validate_before_next_update: gmod_fullvalidatebeforenextupdate,
+		bytes_downloaded: gmod_bytesdownloaded,
+		bytes_to_download: gmod_bytestodownload,
+		bytes_staged: gmod_bytesstaged,
+		bytes_to_stage: gmod_bytestostage
+	});
 
 	if gmod_stateflags != 4 || gmod_scheduledautoupdate != 0 || gmod_fullvalidatebeforenextupdate || gmod_bytesdownloaded != gmod_bytestodownload || gmod_bytesstaged != gmod_bytestostage {
 		return Err(AlmightyError::Generic("Garry's Mod is Not Ready. Check Steam > Downloads and make sure it is fully installed and up to date. If that doesn't work, try launching the game, closing it, then running the tool again.".to_string()));
 	}
 
+	Ok(())
+}
+
+// Makes sure a GMod install is ready to patch, and finds its directory and branch
+fn get_gmod_install_dir<W>(writer: fn() -> W, writer_is_interactive: bool, gmod_install: &steam::AppInstall) -> Result<(PathBuf, String), AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let gmod_steam_library_path_str = gmod_install.library_path.to_string_lossy();
+
+	emit(writer, writer_is_interactive, Event::GmodLibrary { path: &gmod_steam_library_path_str });
+
+	check_gmod_app_state(writer, writer_is_interactive, &gmod_install.manifest)?;
+
 	// Get GMod branch
 	// TODO: Change branch to x86-64 if the current branch isn't in the manifest
-	let gmod_mountedconfig = gmod_manifest.mounted_config;
-	let gmod_branch = gmod_mountedconfig.beta_key;
-	let gmod_branch = if let Some(gmod_branch) = gmod_branch { gmod_branch } else { "public".to_string() };
+	let gmod_branch = gmod_install.manifest.mounted_config.beta_key.clone().unwrap_or("public".to_string());
 
-	terminal_write(writer, format!("GMod Beta Branch: {gmod_branch}\n").as_str(), true, None);
+	emit(writer, writer_is_interactive, Event::GmodBranch { branch: &gmod_branch });
 
 	// Get GMod path
-	// TODO: What about `steamapps/<username>/GarrysMod`? Is that still a thing, or did SteamPipe kill/migrate it completely?
-	let gmod_path_config = gmod_manifest.install_dir;
-	let mut gmod_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_steam_library_path.clone(), &["steamapps", "common", &gmod_path_config]), true);
+	let gmod_path = gmod_install.find_install_dir();
 
-	// Try SteamApps with capitalization
-	if gmod_path.is_err() {
-		gmod_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_steam_library_path.clone(), &["SteamApps", "common", &gmod_path_config]), true);
+	if gmod_path.is_none() {
+		return Err(AlmightyError::Generic("Couldn't find Garry's Mod directory. Is Garry's Mod installed?".to_string()));
 	}
 
-	if gmod_path.is_err() {
-		return Err(AlmightyError::Generic("Couldn't find Garry's Mod directory. Is Garry's Mod installed?".to_string()));
+	Ok((gmod_path.unwrap(), gmod_branch))
+}
+
+// List GMod's branches, and warn if the installed build isn't the latest one Steam knows about for its branch
+// Patches are made for the latest builds, so they may not match an install that's behind (or ahead of Steam's cache)
+fn check_gmod_build<W>(writer: fn() -> W, writer_is_interactive: bool, steam_installation: &SteamInstallation, gmod_install: &steam::AppInstall, gmod_branch: &str)
+where
+	W: std::io::Write + 'static
+{
+	let gmod_branches = match steam_installation.read_app_branches(steam::GMOD_APP_ID) {
+		Ok(gmod_branches) => gmod_branches,
+		Err(error) => {
+			terminal_write(writer, format!("Warning: {error}\n").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			return;
+		}
+	};
+
+	// Steam hasn't cached GMod's app info yet
+	if gmod_branches.is_empty() {
+		return;
+	}
+
+	emit(writer, writer_is_interactive, Event::GmodBranches {
+		branches: gmod_branches.iter().map(|gmod_branch_info| ListedGmodBranch {
+			name: gmod_branch_info.name.clone(),
+			build_id: gmod_branch_info.build_id,
+			description: gmod_branch_info.description.clone(),
+			password_required: gmod_branch_info.password_required,
+			installed: gmod_branch_info.name == gmod_branch
+		}).collect()
+	});
+
+	let gmod_build_id = gmod_install.manifest.build_id;
+
+	match gmod_branches.iter().find(|gmod_branch_info| gmod_branch_info.name == gmod_branch) {
+		Some(gmod_branch_info) if gmod_branch_info.build_id != gmod_build_id => {
+			terminal_write(writer, format!("Warning: Garry's Mod is on Build {gmod_build_id}, but the latest {gmod_branch} build Steam knows about is Build {}.\n\tIf Steam has an update queued for it, let that finish before patching.\n", gmod_branch_info.build_id).as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+		},
+		Some(_) => {},
+		None => {
+			terminal_write(writer, format!("Warning: Steam doesn't list Garry's Mod's {gmod_branch} branch anymore. You may need to switch to another one in Steam > Garry's Mod > Properties > Betas.\n").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+		}
 	}
+}
 
-	let gmod_path = gmod_path.unwrap();
+// Patches (or restores, or checks) one GarrysMod directory
+#[allow(clippy::too_many_arguments)]
+async fn patch_gmod_dir<W>(
+	writer: fn() -> W,
+	writer_is_interactive: bool,
+	args: &Args,
+	downloader: &Arc<download::Downloader>,
+	mirrors: &MirrorList,
+	offline_bundle: Option<&bundle::OfflineBundle>,
+	remote_manifest: &Manifest,
+	gmod_path: &Path,
+	gmod_branch: &str,
+	platform_masked: &str,
+	#[cfg_attr(not(unix), expect(unused_variables, reason = "only used on unix"))]
+	root: bool,
+	dry_run: bool,
+	now: Instant
+) -> Result<RunOutcome, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let gmod_path = gmod_path.to_path_buf();
 	let gmod_path_str = gmod_path.to_string_lossy();
+	let gmod_branch = gmod_branch.to_string();
 
-	terminal_write(writer, format!("GMod Path: {gmod_path_str}\n").as_str(), true, None);
+	emit(writer, writer_is_interactive, Event::GmodPath { path: &gmod_path_str });
 
 	// Abort if they're running as root AND the GMod directory isn't owned by root
 	// Will hopefully prevent broken installs/updating
@@ -1066,429 +2102,1182 @@ where
 		}
 	}
 
-	// Determine target platform
-	// Get GMod CompatTool config (Steam Linux Runtime, Proton, etc) on Linux
-	// NOTE: platform_masked is specifically for Proton
-	let platform = if cfg!(windows) { "windows" } else if cfg!(target_os = "macos") { "macos" } else { "linux" };
+	// Check if the last patch run was interrupted (crashed, killed, lost power, etc) or failed partway through
+	// By default we just resume, since the integrity check below picks up wherever it left off
+	let backup_dir = backup::get_backup_dir(&gmod_path);
+	if let Some(journal_entries) = journal::read_interrupted_journal(&backup_dir) {
+		match journal_entries.last() {
+			Some(journal::JournalEntry::Failed { error }) => {
+				terminal_write(writer, format!("WARNING: The last patch run failed partway through: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			},
+			_ => {
+				terminal_write(writer, "WARNING: The last patch run didn't finish!", true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			}
+		}
 
-	#[cfg_attr(not(target_os = "linux"), expect(unused_mut, reason = "used on linux"))]
-	let mut platform_masked = platform;
+		// Clean up any temp files it was in the middle of writing
+		if !dry_run {
+			for journal_entry in &journal_entries {
+				if let journal::JournalEntry::Start { filename, .. } = journal_entry {
+					let gmod_file_parts: Vec<&str> = filename.split("/").collect();
+					let gmod_file_path = extend_pathbuf_and_return(gmod_path.clone(), &gmod_file_parts[..]);
+					let _ = std::fs::remove_file(journal::get_temp_file_path(&gmod_file_path));
+				}
+			}
+		}
 
-	#[cfg_attr(not(target_os = "linux"), expect(unused_mut, reason = "used on linux"))]
-	let mut gmod_compattool = "none".to_string();
+		if args.rollback_interrupted && !dry_run {
+			rollback_interrupted_run(writer, writer_is_interactive, &gmod_path, &backup_dir, &journal_entries)?;
 
-	#[cfg(target_os = "linux")]
-	{
-		// Get Steam config
-		let steam_config_path = extend_pathbuf_and_return(steam_path.clone(), &["config", "config.vdf"]);
-		let steam_config_str = tokio::fs::read_to_string(steam_config_path).await;
+			if let Err(error) = journal::clear_journal(&backup_dir) {
+				return Err(AlmightyError::Generic(format!("Failed to clear patch journal: {error}")));
+			}
+
+			let now = now.elapsed().as_secs_f64();
+			terminal_write(writer, format!("\nInterrupted patch run rolled back successfully! Took {now} second(s).").as_str(), true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
 
-		if steam_config_str.is_err() {
-			return Err(AlmightyError::Generic("Couldn't find Steam config.vdf. Have you ever launched/signed in to Steam?".to_string()));
+			return Ok(RunOutcome::Done);
 		}
 
-		let steam_config_str = steam_config_str.unwrap();
-		let steam_config = vdf::from_str(steam_config_str.as_str());
+		terminal_write(writer, "\tResuming where it left off. To undo it instead, run the tool with --rollback-interrupted.\n", true, None, MessageLevel::Info);
+	}
 
-		if steam_config.is_err() {
-			return Err(AlmightyError::Generic("Couldn't parse Steam config.vdf. Is the file corrupt?".to_string()));
-		}
+	let platform_branches = remote_manifest.get(platform_masked);
+	if platform_branches.is_none() {
+		return Err(AlmightyError::Generic(format!("This operating system ({platform_masked}) is not supported!")));
+	}
 
-		let steam_config: SteamConfig = steam_config.unwrap();
-		let steam_config = steam_config.software.valve.steam;
+	let platform_branch_files = platform_branches.unwrap().get(&gmod_branch);
+	if platform_branch_files.is_none() {
+		return Err(AlmightyError::Generic(format!("This Beta Branch of Garry's Mod ({gmod_branch}) is not supported! Please go to Steam > Garry's Mod > Properties > Betas, select the x86-64 beta, then try again.")));
+	}
 
-		if let Some(steam_config_compat_tool_mapping) = steam_config.compat_tool_mapping {
-			if let Some(steam_config_compat_tool_mapping_gmod) = steam_config_compat_tool_mapping.gmod {
-				let compattool = steam_config_compat_tool_mapping_gmod.name.to_lowercase();
+	let platform_branch_files = platform_branch_files.unwrap();
 
-				if compattool.contains("proton") {
-					platform_masked = "windows";
-				}
+	if args.command == Some(Command::Restore) {
+		return restore_files(writer, writer_is_interactive, downloader, mirrors, offline_bundle, &gmod_path, platform_masked, &gmod_branch, platform_branch_files, args.disable_cache, dry_run, now).await;
+	}
+
+	// Determine file integrity status
+	terminal_write(writer, "Determining file integrity status...", true, None, MessageLevel::Info);
+
+	// TODO: phf_map for these
+	let integrity_status_strings = HashMap::from([
+		(IntegrityStatus::NeedDelete, "Needs Delete"),
+		(IntegrityStatus::NeedOriginal, "Needs Original + Fix"),
+		(IntegrityStatus::NeedWipeFix, "Needs Wipe + Fix"),
+		(IntegrityStatus::NeedFix, "Needs Fix"),
+		(IntegrityStatus::Fixed, "Already Fixed")
+	]);
+
+	#[allow(clippy::type_complexity)]
+	let integrity_results: Vec<(&String, Result<IntegrityStatus, String>, &IndexMap<String, String>)> = platform_branch_files.par_iter()
+	.map(|(filename, hashes)| {
+		let integrity_result;
+		if args.no_sourcescheme && filename.ends_with(".res") {
+			emit(writer, writer_is_interactive, Event::FileStatusSkipped { filename, reason: "--no-sourcescheme" });
+			integrity_result = Ok(IntegrityStatus::Fixed);
+		} else {
+			integrity_result = determine_file_integrity_status(gmod_path.clone(), filename, hashes);
+			let integrity_result_clone = integrity_result.clone();
 
-				gmod_compattool = compattool;
+			match integrity_result_clone {
+				Ok(integrity_result_clone) => {
+					let integrity_status_string = integrity_status_strings[&integrity_result_clone];
+					emit(writer, writer_is_interactive, Event::FileStatus { filename, status: format!("{integrity_result_clone:?}").as_str(), description: integrity_status_string });
+				},
+				Err(error) => {
+					emit(writer, writer_is_interactive, Event::FileStatusFailed { filename, error: &error });
+				}
 			}
 		}
-	}
-
-	terminal_write(writer, format!("Target Platform: {platform_masked} ({gmod_compattool})\n").as_str(), true, None);
 
-	// Warn if -nochromium is in launch options
-	// Some GMod "menu error fix" guides include it + gmod-lua-menu
-	let steam_user_localconfig_path = extend_pathbuf_and_return(steam_path.clone(), &["userdata", steam_id.account_id().into_u32().to_string().as_str(), "config", "localconfig.vdf"]);
-	let steam_user_localconfig_str = tokio::fs::read_to_string(steam_user_localconfig_path).await;
+		(filename, integrity_result, hashes)
+	}).collect();
 
-	if steam_user_localconfig_str.is_err() {
-		return Err(AlmightyError::Generic("Couldn't find Steam localconfig.vdf. Have you ever launched/signed in to Steam?".to_string()));
+	// Filter out fixed files, and if there were any i/o errors getting the hash, exit early
+	// We don't exit during the multithreaded iterator above because we want *all* of the failing files to list first
+	let mut pending_files: Vec<(&String, IntegrityStatus, &IndexMap<String, String>)> = vec![];
+	for (filename, result, hashes) in integrity_results {
+		match result {
+			Ok(result) => {
+				if result != IntegrityStatus::Fixed {
+					pending_files.push((filename, result, hashes));
+				}
+			},
+			Err(_) => {
+				return Err(AlmightyError::Generic("Failed to get integrity status of one or more files!".to_string()));
+			}
+		}
+	}
+
+	if dry_run {
+		return Ok(print_patch_plan(writer, writer_is_interactive, &integrity_status_strings, &pending_files, platform_branch_files, &gmod_path, platform_masked, &gmod_branch, args.disable_cache));
+	}
+
+	let pending_files_len = pending_files.len();
+	if pending_files_len > 0 {
+		let cache_dir = get_cache_dir(writer, writer_is_interactive, args.disable_cache).await?;
+
+		// Download what we need
+		terminal_write(writer, "Downloading patch files...", true, None, MessageLevel::Info);
+
+		// Filename on the mirrors, hash, and size
+		let mut downloads: Vec<(String, String, Option<u64>)> = vec![];
+		for (filename, integrity_status, hashes) in &pending_files {
+			// Need Original
+			if *integrity_status == IntegrityStatus::NeedOriginal {
+				downloads.push((format!("originals/{platform_masked}/{gmod_branch}/{filename}.zst"), hashes["original"].clone(), get_manifest_size(hashes, "original_size")));
+			}
+
+			// Need Fix (we filtered out IntegrityStatus::Fixed above, but we still need IntegrityStatus::NeedDelete for later)
+			if *integrity_status != IntegrityStatus::NeedDelete {
+				downloads.push((format!("patches/{platform_masked}/{gmod_branch}/{filename}.bsdiff"), hashes["patch"].clone(), get_manifest_size(hashes, "patch_size")));
+			}
+		}
+
+		extract_from_offline_bundle(offline_bundle, &downloads)?;
+
+		let total_progress = Arc::new(TotalProgress::new(0));
+		let mut download_futures = JoinSet::new();
+		for (filename, hash, size) in downloads {
+			total_progress.add_expected(size.unwrap_or_default());
+			download_futures.spawn(download_file_to_cache(writer, writer_is_interactive, downloader.clone(), mirrors.binary.clone(), cache_dir.clone(), filename, hash, size, total_progress.clone()));
+		}
+
+		while let Some(download_result) = download_futures.join_next().await {
+			if download_result.is_err() {
+				return Err(AlmightyError::Generic("Failed to download one or more patch files!".to_string()));
+			}
+		}
+
+		// Back up anything we're about to overwrite or delete, so it can be restored later
+		// This is done up front so we never touch the GMod directory if a backup fails
+		let backup_dir_str = backup_dir.to_string_lossy();
+
+		terminal_write(writer, format!("\nBacking up {pending_files_len} file(s) to: {backup_dir_str}").as_str(), true, None, MessageLevel::Info);
+
+		let journal = match journal::Journal::begin(&backup_dir, &gmod_path, platform_masked, &gmod_branch) {
+			Ok(journal) => journal,
+			Err(error) => {
+				return Err(AlmightyError::Generic(format!("Failed to create patch journal: {error}")));
+			}
+		};
+
+		// Anything that goes wrong from here on is recorded in the journal, so the next run doesn't mistake it for a crash
+		let backup_and_patch_files = || -> Result<(), AlmightyError> {
+			let mut backup_index = backup::read_backup_index(&backup_dir, &gmod_path);
+			for (filename, _, hashes) in &pending_files {
+				let original_hash = &hashes["original"];
+
+				let journal_entry = match backup::backup_file(&backup_dir, &gmod_path, &backup_index, filename, original_hash) {
+					Ok(backup::BackupResult::BackedUp) => {
+						terminal_write(writer, format!("\tBacked Up: {filename}").as_str(), true, None, MessageLevel::Info);
+						backup_index.files.insert(filename.to_string(), original_hash.clone());
+
+						Some(journal::JournalEntry::Backup { filename: filename.to_string(), hash: original_hash.clone() })
+					},
+					Ok(backup::BackupResult::AlreadyBackedUp) => Some(journal::JournalEntry::Backup { filename: filename.to_string(), hash: original_hash.clone() }),
+					// Rolling back to the original wouldn't put it back the way it was before the run (restore does that)
+					Ok(backup::BackupResult::NotOriginal) => Some(journal::JournalEntry::NotBackedUp { filename: filename.to_string() }),
+					Ok(backup::BackupResult::Missing) => None,
+					Err(error) => {
+						return Err(AlmightyError::Generic(format!("Failed to back up {filename}: {error}")));
+					}
+				};
+
+				if let Some(journal_entry) = journal_entry && let Err(error) = journal.record(&journal_entry) {
+					return Err(AlmightyError::Generic(format!("Failed to write patch journal: {error}")));
+				}
+			}
+
+			if let Err(error) = backup::write_backup_index(&backup_dir, &backup_index) {
+				return Err(AlmightyError::Generic(format!("Failed to write backup index: {error}")));
+			}
+
+			// Patch the files
+			terminal_write(writer, format!("\nPatching {pending_files_len} file(s)...").as_str(), true, None, MessageLevel::Info);
+
+			// TODO: Early exit if any patches fail
+			let patch_results: Vec<(&String, IntegrityStatus)> = pending_files.par_iter()
+			.map(|(filename, integrity_status, hashes)| {
+				let new_integrity_status = patch_file(
+					writer,
+					writer_is_interactive,
+					&integrity_status_strings,
+					&gmod_path,
+					platform_masked,
+					&gmod_branch,
+					&cache_dir,
+					&journal,
+					filename,
+					integrity_status,
+					hashes
+				);
+
+				(*filename, new_integrity_status)
+			}).collect();
+
+			for (_, integrity_status) in patch_results {
+				if integrity_status != IntegrityStatus::Fixed {
+					return Err(AlmightyError::Generic("Failed to patch one or more files!".to_string()));
+				}
+			}
+
+			Ok(())
+		};
+
+		if let Err(error) = backup_and_patch_files() {
+			if let Err(journal_error) = journal.fail(&error.to_string()) {
+				terminal_write(writer, format!("Failed to write patch journal: {journal_error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			}
+
+			return Err(error);
+		}
+
+		if let Err(error) = journal.finish() {
+			terminal_write(writer, format!("Failed to remove patch journal: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+		}
+
+		if args.disable_cache {
+			let remove_result = tokio::fs::remove_dir_all(cache_dir).await;
+
+			match remove_result {
+				Ok(_) => {
+					terminal_write(writer,"\n[disable-cache:Post] Successfully cleared GModPatchTool cache directory.", true, None, MessageLevel::Info);
+				},
+				Err(error) => {
+					terminal_write(writer, format!("\n[disable-cache:Post] Failed to clear GModPatchTool cache directory: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+				}
+			}
+		}
+	} else {
+		terminal_write(writer, "No files need patching!", true, None, MessageLevel::Info);
+	}
+
+	// Make sure executables are executable on Linux and macOS
+	// TODO: Windows support...but at the time of writing it's not well supported in Rust
+	// This is done separately because we want it to apply to ALL files regardless of if they needed to be patched
+	// https://github.com/solsticegamestudios/GModPatchTool/issues/161
+	#[cfg(unix)]
+	{
+		terminal_write(writer, "\nApplying file permissions...", true, None, MessageLevel::Info);
+
+		for (filename, fileinfo) in platform_branch_files {
+			let executable = fileinfo.get("executable");
+
+			if let Some(executable) = executable {
+				if executable == "true" {
+					let gmod_file_parts: Vec<&str> = filename.split("/").collect();
+					let gmod_file_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_path.clone(), &gmod_file_parts[..]), true);
+
+					if let Ok(gmod_file_path) = gmod_file_path {
+						let metadata = tokio::fs::metadata(&gmod_file_path).await;
+
+						match metadata {
+							Ok(metadata) => {
+								// Ensure the executable bit is present and apply it to the file
+								let mut perms = metadata.permissions();
+								perms.set_mode(perms.mode() | 0o111);
+								let perms_result: Result<(), io::Error> = tokio::fs::set_permissions(&gmod_file_path, perms).await;
+
+								match perms_result {
+									Ok(_) => {
+										terminal_write(writer, format!("\t{filename}").as_str(), true, None, MessageLevel::Info);
+									},
+									Err(error) => {
+										terminal_write(writer, format!("\tFailed to Apply Permissions: {filename} | {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+										// TODO: Fatal?
+									}
+								}
+							},
+							Err(error) => {
+								terminal_write(writer, format!("\tFailed to Apply Permissions: {filename} | {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+								// TODO: Fatal?
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	// Delete ChromiumCache/ChromiumCacheMultirun
+	// Solves issues with being corrupt/stuck lockfiles, and GMod MUST NOT be running for this tool to run, so it probably solves more issues than it could create
+	if !args.skip_clear_chromiumcache {
+		let gmod_chromiumcache_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_path.clone(), &["ChromiumCache"]), false);
+		if let Ok(gmod_chromiumcache_path) = gmod_chromiumcache_path {
+			terminal_write(writer, "\nClearing ChromiumCache...", true, None, MessageLevel::Info);
+			if let Err(error) = tokio::fs::remove_dir_all(gmod_chromiumcache_path).await {
+				terminal_write(writer, format!("\tFailed: {error}\n\tYou may want to delete ChromiumCache from the GarrysMod directory manually!").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			} else {
+				terminal_write(writer, "Done!", true, None, MessageLevel::Info);
+			}
+		}
+
+		let gmod_chromiumcachemultirun_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_path.clone(), &["ChromiumCacheMultirun"]), false);
+		if let Ok(gmod_chromiumcachemultirun_path) = gmod_chromiumcachemultirun_path {
+			terminal_write(writer, "\nClearing ChromiumCacheMultirun...", true, None, MessageLevel::Info);
+			if let Err(error) = tokio::fs::remove_dir_all(gmod_chromiumcachemultirun_path).await {
+				terminal_write(writer, format!("\tFailed: {error}\n\tYou may want to delete ChromiumCacheMultirun from the GarrysMod directory manually!").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			} else {
+				terminal_write(writer, "Done!", true, None, MessageLevel::Info);
+			}
+		}
+	}
+
+	// TODO: Update BASS? https://github.com/Facepunch/garrysmod-requests/issues/1885
+	// TODO: Check dxlevel/d3d9ex support in Proton, and if there's anything we can do about it
+
+	let now = now.elapsed().as_secs_f64();
+	terminal_write(writer, format!("\nGModPatchTool applied successfully! Took {now} second(s).").as_str(), true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
+
+	Ok(RunOutcome::Patched)
+}
+
+// On Linux, Steam's registry.vdf says what's running, as long as steam.pid says Steam is too
+// Otherwise (no registry.vdf or steam.pid, Flatpak, Windows, macOS), or if Steam isn't running, look for GMod's process
+fn is_gmod_running() -> bool {
+	#[cfg(target_os = "linux")]
+	if let Some(gmod_running) = steam::read_registry().and_then(|steam_registry| steam_registry.is_app_running(steam::GMOD_APP_ID)) {
+		return gmod_running;
+	}
+
+	let sys = System::new_with_specifics(sysinfo::RefreshKind::nothing().with_processes(sysinfo::ProcessRefreshKind::nothing()));
+
+	sys.processes_by_exact_name("gmod.exe".as_ref()).next().is_some() || sys.processes_by_exact_name("gmod".as_ref()).next().is_some()
+}
+
+// On Linux, steam.pid says whether Steam is running
+// Otherwise (no registry.vdf or steam.pid, Flatpak, Windows, macOS), look for its process
+fn is_steam_running() -> bool {
+	#[cfg(target_os = "linux")]
+	if let Some(steam_registry) = steam::read_registry() && steam_registry.pid.is_some() {
+		return steam_registry.is_steam_running();
+	}
+
+	let sys = System::new_with_specifics(sysinfo::RefreshKind::nothing().with_processes(sysinfo::ProcessRefreshKind::nothing()));
+
+	["steam", "steam.exe", "steam_osx"].iter().any(|steam_process_name| sys.processes_by_exact_name(steam_process_name.as_ref()).next().is_some())
+}
+
+// --steam-install, otherwise the one GMod is in
+fn find_steam_installation(args: &Args) -> Result<SteamInstallation, AlmightyError> {
+	let steam_installations = steam::find_installations();
+
+	match &args.steam_install {
+		Some(steam_install_arg) => get_steam_installation_arg(steam_installations, steam_install_arg),
+		None => get_default_steam_installation_id(&steam_installations)
+			.and_then(|steam_installation_id| steam_installations.into_iter().nth(steam_installation_id))
+			.ok_or_else(|| AlmightyError::Generic("Couldn't find Steam. If it's installed, try using the --steam-install argument to force a specific path.".to_string()))
+	}
+}
+
+// launch-options: list, add, or remove GMod's Launch Options in localconfig.vdf
+async fn edit_launch_options<W>(writer: fn() -> W, writer_is_interactive: bool, args: &Args, action: &LaunchOptionsAction) -> Result<RunOutcome, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	// Steam keeps its own copy in memory, and writes it over ours when it closes
+	if *action != LaunchOptionsAction::List && is_steam_running() {
+		return Err(AlmightyError::Generic("Steam is running, so it would undo any changes to the Launch Options when it closes. Please exit Steam completely (Steam > Exit), then try again.".to_string()));
+	}
+
+	let steam_installation = find_steam_installation(args)?;
+
+	emit(writer, writer_is_interactive, Event::SteamPath { path: &steam_installation.path.to_string_lossy() });
+
+	// Same user the patch run checks
+	let gmod_installs = steam_installation.find_gmod_installs().unwrap_or_default();
+	let gmod_last_owner = gmod_installs.iter().find(|gmod_install| gmod_install.listed_by_steam).or(gmod_installs.first()).map(|gmod_install| gmod_install.manifest.last_owner).unwrap_or_default();
+	let steam_id = get_steam_user(writer, writer_is_interactive, &steam_installation, args.steam_user.as_deref(), gmod_last_owner)?;
+
+	let localconfig_path = get_localconfig_path(&steam_installation.path, &steam_id);
+	let localconfig_str = tokio::fs::read_to_string(&localconfig_path).await
+		.map_err(|_| AlmightyError::Generic("Couldn't find Steam localconfig.vdf. Have you ever launched/signed in to Steam?".to_string()))?;
+
+	let mut gmod_launch_options = launch_options::LaunchOptions::parse(&parse_gmod_launch_options(localconfig_str.clone())?.unwrap_or_default());
+
+	match action {
+		LaunchOptionsAction::List => {
+			emit(writer, writer_is_interactive, Event::LaunchOptions { launch_options: &gmod_launch_options.to_string(), items: gmod_launch_options.items() });
+			return Ok(RunOutcome::Done);
+		},
+		LaunchOptionsAction::Add { option } => {
+			let new_launch_options = launch_options::LaunchOptions::parse_input(&option.join(" "));
+
+			if new_launch_options.is_empty() {
+				return Err(AlmightyError::Generic("There's no Launch Option to add.".to_string()));
+			}
+
+			gmod_launch_options.add(&new_launch_options);
+		},
+		LaunchOptionsAction::Remove { option } => {
+			if !gmod_launch_options.remove(option) {
+				terminal_write(writer, format!("{option} isn't in Garry's Mod's Launch Options, so there's nothing to remove.").as_str(), true, None, MessageLevel::Info);
+				return Ok(RunOutcome::Done);
+			}
+		}
+	}
+
+	let gmod_launch_options_str = gmod_launch_options.to_string();
+	let new_localconfig_str = launch_options::set_localconfig_launch_options(&localconfig_str, steam::GMOD_APP_ID, &gmod_launch_options_str)
+		.map_err(|error| AlmightyError::Generic(format!("Couldn't edit Steam localconfig.vdf. Is the file corrupt?\n\t{error}")))?;
+
+	// Make sure Steam will read back exactly what we meant
+	if parse_gmod_launch_options(new_localconfig_str.clone())?.as_deref() != Some(gmod_launch_options_str.as_str()) {
+		return Err(AlmightyError::Generic("Couldn't edit Steam localconfig.vdf safely, so it wasn't changed. Please edit the Launch Options in Steam > Garry's Mod > Properties > General instead.".to_string()));
+	}
+
+	let localconfig_backup_path = localconfig_path.with_extension("vdf.gmodpatchtool.bak");
+	tokio::fs::write(&localconfig_backup_path, &localconfig_str).await
+		.map_err(|error| AlmightyError::Generic(format!("Couldn't back up Steam localconfig.vdf, so it wasn't changed: {error}")))?;
+
+	// Written next to it first, so it's never left half written
+	let localconfig_new_path = localconfig_path.with_extension("vdf.gmodpatchtool.new");
+	tokio::fs::write(&localconfig_new_path, &new_localconfig_str).await
+		.map_err(|error| AlmightyError::Generic(format!("Couldn't write Steam localconfig.vdf: {error}")))?;
+	tokio::fs::rename(&localconfig_new_path, &localconfig_path).await
+		.map_err(|error| AlmightyError::Generic(format!("Couldn't write Steam localconfig.vdf: {error}")))?;
+
+	emit(writer, writer_is_interactive, Event::LaunchOptions { launch_options: &gmod_launch_options_str, items: gmod_launch_options.items() });
+
+	terminal_write(writer, format!("Garry's Mod's Launch Options were saved! The old localconfig.vdf was backed up to:\n\t{}", localconfig_backup_path.display()).as_str(), true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
+
+	Ok(RunOutcome::Done)
+}
+
+// Gets the Steam User whose launch options we check
+fn get_steam_user<W>(writer: fn() -> W, writer_is_interactive: bool, steam_installation: &SteamInstallation, steam_user_arg: Option<&str>, gmod_last_owner: u64) -> Result<SteamId, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let steam_users = steam_installation.read_users().map_err(AlmightyError::Generic)?;
+	let steam_user_id = steam::select_user(&steam_users, steam_user_arg, gmod_last_owner).map_err(AlmightyError::Generic)?;
+
+	// Let them know which one we picked, and how to pick a different one
+	if steam_users.len() > 1 {
+		let mut steam_users_str: String = "".to_string();
+		for (other_steam_user_id, other_steam_user) in steam_users.iter().enumerate() {
+			steam_users_str += format!("\n\t- {} ({} / {})", other_steam_user.persona_name, other_steam_user.steam_id_64, other_steam_user.account_name).as_str();
+
+			if other_steam_user_id == steam_user_id {
+				steam_users_str += " [Using This One]";
+			}
+		}
+
+		terminal_write(writer, format!("Multiple Steam Users Detected!{steam_users_str}\n\tTo use a different one, run the tool with --steam-user <SteamID64 or account name>.\n").as_str(), true, None, MessageLevel::Info);
+	}
+
+	let steam_user = &steam_users[steam_user_id];
+	let steam_id_64 = &steam_user.steam_id_64;
+	let steam_id = SteamId::new(steam_id_64.parse::<u64>().unwrap_or_default());
+
+	if steam_id.is_err() {
+		return Err(AlmightyError::Generic(format!("Steam User {} has an invalid SteamID64 ({steam_id_64}). Is loginusers.vdf corrupt?", steam_user.persona_name)));
+	}
+
+	let steam_id = steam_id.unwrap();
+
+	emit(writer, writer_is_interactive, Event::SteamUser { persona_name: &steam_user.persona_name, steam_id_64, steam3_id: &steam_id.steam3id() });
+
+	Ok(steam_id)
+}
+
+// Warnings worth repeating in the run summary, since they're easy to miss among the file output
+fn summary_warning<W>(writer: fn() -> W, writer_is_interactive: bool, run_warnings: &mut Vec<String>, warning: String)
+where
+	W: std::io::Write + 'static
+{
+	terminal_write(writer, format!("Warning: {warning}\n").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+	run_warnings.push(warning);
+}
+
+// Report which Proton GMod uses, and warn if its prefix isn't ready for it
+// Patched CEF under a missing/broken prefix is the most common issue people come to us with
+#[cfg(target_os = "linux")]
+fn check_proton<W>(writer: fn() -> W, writer_is_interactive: bool, steam_installation: &SteamInstallation, compat_tool_name: &str, gmod_library_path: Option<&Path>, run_warnings: &mut Vec<String>)
+where
+	W: std::io::Write + 'static
+{
+	let compat_tool = steam_installation.find_compat_tool(compat_tool_name);
+	let compat_tool_path_str = compat_tool.path.as_ref().map(|path| path.to_string_lossy());
+
+	emit(writer, writer_is_interactive, Event::CompatTool { name: &compat_tool.name, path: compat_tool_path_str.as_deref(), version: compat_tool.version.as_deref() });
+
+	if compat_tool.path.is_none() {
+		summary_warning(writer, writer_is_interactive, run_warnings, format!("Couldn't find {} in your Steam Libraries or compatibilitytools.d. Steam may still be downloading it; if not, pick a different one in Garry's Mod > Properties > Compatibility.", compat_tool.name));
+	}
+
+	let compat_prefix = gmod_library_path.and_then(|gmod_library_path| steam::SteamLibrary::new(gmod_library_path.to_path_buf()).read_compat_prefix(steam::GMOD_APP_ID));
+
+	let Some(compat_prefix) = compat_prefix else {
+		summary_warning(writer, writer_is_interactive, run_warnings, "Garry's Mod doesn't have a Proton prefix yet. Launch Garry's Mod once through Steam and close it, then run GModPatchTool again.".to_string());
+		return;
+	};
+
+	let compat_prefix_path_str = compat_prefix.path.to_string_lossy();
+
+	emit(writer, writer_is_interactive, Event::CompatPrefix { path: &compat_prefix_path_str, initialized: compat_prefix.initialized, version: compat_prefix.version.as_deref() });
+
+	if !compat_prefix.initialized {
+		summary_warning(writer, writer_is_interactive, run_warnings, "Garry's Mod's Proton prefix isn't fully set up. Launch Garry's Mod once through Steam and close it, then run GModPatchTool again.".to_string());
+	} else if let (Some(compat_tool_major_version), Some(compat_prefix_major_version)) = (compat_tool.major_version(), compat_prefix.major_version()) && compat_tool_major_version > compat_prefix_major_version + PROTON_PREFIX_MAX_VERSION_GAP {
+		summary_warning(writer, writer_is_interactive, run_warnings, format!("Garry's Mod's Proton prefix was set up by a much older Proton ({}) than the one it uses now ({}). If Garry's Mod has issues after patching, delete {} and launch Garry's Mod again so Proton recreates it.", compat_prefix.version.as_deref().unwrap_or_default(), compat_tool.version.as_deref().unwrap_or_default(), compat_prefix.path.parent().unwrap_or(&compat_prefix.path).to_string_lossy()));
+	}
+}
+
+// Report which Steam Linux Runtime container native GMod runs in, and warn about setups known to break in it
+// Returns the container, for the run summary
+#[cfg(target_os = "linux")]
+fn check_linux_runtime<W>(writer: fn() -> W, writer_is_interactive: bool, steam_installation: &SteamInstallation, linux_runtime: steam::LinuxRuntime, compat_tool_name: &str, gmod_launch_options: Option<&launch_options::LaunchOptions>, run_warnings: &mut Vec<String>) -> &'static str
+where
+	W: std::io::Write + 'static
+{
+	let compat_tool = steam_installation.find_compat_tool(compat_tool_name);
+	let container = linux_runtime.container();
+
+	emit(writer, writer_is_interactive, Event::LinuxRuntime { compat_tool: &compat_tool.name, container, version: compat_tool.version.as_deref() });
+
+	if compat_tool.path.is_none() {
+		summary_warning(writer, writer_is_interactive, run_warnings, format!("Couldn't find {} in your Steam Libraries. Steam may still be downloading it.", compat_tool.name));
+	}
+
+	// GMod's launcher script (hl2.sh) drops LD_PRELOAD unless GMOD_ENABLE_LD_PRELOAD=1 is set (the launch option rules cover that part)
+	// When it is, pressure-vessel tries to bring the preloaded libraries into the container, and they're built against the host's libraries, not the container's
+	let gmod_enable_ld_preload = gmod_launch_options.is_some_and(launch_options::LaunchOptions::enables_ld_preload);
+
+	if gmod_enable_ld_preload {
+		summary_warning(writer, writer_is_interactive, run_warnings, format!("Garry's Mod's Launch Options enable LD_PRELOAD (GMOD_ENABLE_LD_PRELOAD) while it runs in the {container} container. Preloaded libraries from your system often fail to load or crash inside pressure-vessel; if Garry's Mod has issues, remove them from Steam > Garry's Mod > Properties > General."));
+	}
+
+	container
+}
+
+// Which GMod build the patches on the mirrors were made for
+// Optional, so mirrors from before builds.json existed (or a bad copy of it) only skip the build check
+async fn get_manifest_builds<W>(writer: fn() -> W, writer_is_interactive: bool, downloader: &download::Downloader, mirrors: &[MirrorRoot]) -> Option<ManifestBuilds>
+where
+	W: std::io::Write + 'static
+{
+	let remote_builds = match get_signed_file_bytes(writer, writer_is_interactive, downloader, mirrors, "builds.json").await {
+		Ok(Some(remote_builds_bytes)) => serde_json::from_slice(&remote_builds_bytes).map_err(|error| error.to_string()),
+		Ok(None) => Err("builds.json is missing".to_string()),
+		Err(error) => Err(error.to_string())
+	};
+
+	match remote_builds {
+		Ok(remote_builds) => Some(remote_builds),
+		Err(error) => {
+			terminal_write(writer, format!("Warning: Couldn't get the Garry's Mod builds the patches were made for, so they aren't checked against yours: {error}\n").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			None
+		}
+	}
+}
+
+// Make sure the patches were made for the GMod build that's installed
+// If GMod updated since, every file it changed looks like it "Needs Original + Fix", and patching would roll it back to our older Original
+#[allow(clippy::too_many_arguments)]
+fn check_manifest_build<W>(writer: fn() -> W, writer_is_interactive: bool, args: &Args, remote_builds: Option<&ManifestBuilds>, platform_masked: &str, gmod_branch: &str, gmod_app_manifest: Option<&steam::SteamAppManifest>, dry_run: bool, run_warnings: &mut Vec<String>) -> Result<(), AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	// Unsupported platforms/branches are reported when patching
+	let Some(remote_build) = remote_builds.and_then(|remote_builds| remote_builds.get(platform_masked)).and_then(|platform_builds| platform_builds.get(gmod_branch)) else {
+		return Ok(());
+	};
+
+	let Some(gmod_app_manifest) = gmod_app_manifest else {
+		terminal_write(writer, format!("Warning: Steam doesn't know about this Garry's Mod directory, so we can't check that it's on Build {} (the one the patches were made for).\n", remote_build.build_id).as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+		return Ok(());
+	};
+
+	let gmod_build_id = gmod_app_manifest.build_id;
+
+	// Depots installed from a different depot manifest than the patches were made for
+	let mismatched_depots: Vec<&str> = remote_build.depots.iter()
+		.filter(|(depot_id, depot_manifest)| gmod_app_manifest.installed_depots.get(*depot_id).is_some_and(|installed_depot| &installed_depot.manifest != *depot_manifest))
+		.map(|(depot_id, _)| depot_id.as_str())
+		.collect();
+
+	if gmod_build_id == remote_build.build_id && mismatched_depots.is_empty() {
+		return Ok(());
+	}
+
+	let mismatch = if gmod_build_id == remote_build.build_id {
+		format!("Garry's Mod is on Build {gmod_build_id} like the patches, but its files from depot(s) {} are from a different depot manifest.", mismatched_depots.join(", "))
+	} else {
+		format!("The patches were made for Garry's Mod Build {}, but it's on Build {gmod_build_id}.", remote_build.build_id)
+	};
+
+	let advice = if gmod_build_id > remote_build.build_id {
+		"Garry's Mod has probably updated since, so patching now would roll the files it changed back to older ones. We update the patches soon after Garry's Mod updates; please try again later."
+	} else {
+		"Garry's Mod is probably out of date, or its files are incomplete. Let Steam update it (or verify its files in Steam > Garry's Mod > Properties > Installed Files), then try again."
+	};
+
+	if dry_run || args.ignore_build_mismatch {
+		let continuing = if dry_run { "" } else { " Continuing anyway, because of --ignore-build-mismatch." };
+		summary_warning(writer, writer_is_interactive, run_warnings, format!("{mismatch} {advice}{continuing}"));
+
+		return Ok(());
+	}
+
+	Err(AlmightyError::Generic(format!("{mismatch}\n{advice}\nIF YOU KNOW WHAT YOU'RE DOING, you can continue anyway by running the tool with --ignore-build-mismatch. Aborting...")))
+}
+
+// Newer launch option rules from the mirrors, on top of the built-in ones
+// Optional, so a mirror without them (or with a bad copy) only means the built-in rules are used
+async fn get_launch_option_rules<W>(writer: fn() -> W, writer_is_interactive: bool, downloader: &download::Downloader, mirrors: &[MirrorRoot]) -> Vec<launch_options::LaunchOptionRule>
+where
+	W: std::io::Write + 'static
+{
+	let builtin_rules = launch_options::parse_rules(launch_options::BUILTIN_RULES.as_bytes()).unwrap();
+
+	let newer_rules = match get_signed_file_bytes(writer, writer_is_interactive, downloader, mirrors, "launch_option_rules.json").await {
+		Ok(Some(rules_bytes)) => launch_options::parse_rules(&rules_bytes),
+		Ok(None) => return builtin_rules,
+		Err(error) => Err(error.to_string())
+	};
+
+	match newer_rules {
+		Ok(newer_rules) => launch_options::merge_rules(builtin_rules, newer_rules),
+		Err(error) => {
+			terminal_write(writer, format!("Warning: Couldn't get the latest Launch Option rules, so the built-in ones are used: {error}\n").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			builtin_rules
+		}
+	}
+}
+
+// Check GMod's launch options against the launch option rules (e.g. -nochromium, which some GMod "menu error fix" guides include along with gmod-lua-menu)
+// Returns GMod's launch options, for the checks that need them
+#[allow(clippy::too_many_arguments)]
+async fn check_launch_options<W>(writer: fn() -> W, writer_is_interactive: bool, downloader: &download::Downloader, mirrors: &[MirrorRoot], steam_path: &Path, steam_id: &SteamId, platform_masked: &str, compat_tool: &str, run_warnings: &mut Vec<String>) -> Result<Option<launch_options::LaunchOptions>, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let gmod_launch_options = read_gmod_launch_options(steam_path, steam_id).await?.map(|gmod_launch_options| launch_options::LaunchOptions::parse(&gmod_launch_options));
+
+	let Some(gmod_launch_options) = gmod_launch_options.filter(|gmod_launch_options| !gmod_launch_options.is_empty()) else {
+		return Ok(None);
+	};
+
+	let rules = get_launch_option_rules(writer, writer_is_interactive, downloader, mirrors).await;
+	let findings = gmod_launch_options.lint(&rules, platform_masked, compat_tool);
+
+	for finding in &findings {
+		emit(writer, writer_is_interactive, Event::LaunchOptionFinding { id: &finding.id, severity: finding.severity, message: &finding.message, fix: &finding.fix });
+
+		if finding.severity >= launch_options::Severity::Warning {
+			run_warnings.push(finding.message.clone());
+		}
+	}
+
+	// Give them a chance to read it, since these break things
+	if findings.iter().any(|finding| finding.severity == launch_options::Severity::Error) {
+		let mut secs_to_continue: u8 = 5;
+		while secs_to_continue > 0 {
+			terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			writer().flush().unwrap();
+			tokio::time::sleep(time::Duration::from_secs(1)).await;
+			secs_to_continue -= 1;
+		}
+
+		// Clear continuing line
+		if writer_is_interactive {
+			terminal_write(writer, "\x1B[0K\n", false, None, MessageLevel::Info);
+		}
+	} else if !findings.is_empty() {
+		terminal_write(writer, "", true, None, MessageLevel::Info); // Newline
+	}
+
+	Ok(Some(gmod_launch_options))
+}
+
+fn get_localconfig_path(steam_path: &Path, steam_id: &SteamId) -> PathBuf {
+	extend_pathbuf_and_return(steam_path.to_path_buf(), &["userdata", steam_id.account_id().into_u32().to_string().as_str(), "config", "localconfig.vdf"])
+}
+
+// GMod's Launch Options from the Steam User's localconfig.vdf
+async fn read_gmod_launch_options(steam_path: &Path, steam_id: &SteamId) -> Result<Option<String>, AlmightyError> {
+	let steam_user_localconfig_path = get_localconfig_path(steam_path, steam_id);
+	let steam_user_localconfig_str = tokio::fs::read_to_string(steam_user_localconfig_path).await;
+
+	if steam_user_localconfig_str.is_err() {
+		return Err(AlmightyError::Generic("Couldn't find Steam localconfig.vdf. Have you ever launched/signed in to Steam?".to_string()));
+	}
+
+	parse_gmod_launch_options(steam_user_localconfig_str.unwrap())
+}
+
+fn parse_gmod_launch_options(steam_user_localconfig_str: String) -> Result<Option<String>, AlmightyError> {
+	let steam_user_localconfig = vdf::from_str(steam_user_localconfig_str.as_str());
+
+	if let Err(error) = steam_user_localconfig {
+		return Err(AlmightyError::Generic(format!("Couldn't parse Steam localconfig.vdf. Is the file corrupt?\n\t{error}")));
+	}
+
+	let steam_user_localconfig: SteamUserLocalConfig = steam_user_localconfig.unwrap();
+	let steam_user_localconfig_gmod = steam_user_localconfig.software.valve.steam.apps.gmod;
+
+	match steam_user_localconfig_gmod {
+		Some(steam_user_localconfig_gmod) => Ok(steam_user_localconfig_gmod.launch_options),
+		None => Err(AlmightyError::Generic("Couldn't find Garry's Mod in user localconfig.vdf. Is Garry's Mod installed?".to_string()))
+	}
+}
+
+// With --gmod-path, Steam may not even be there, so problems with it are only warnings
+fn check_steam_result<T, W>(writer: fn() -> W, writer_is_interactive: bool, gmod_path_mode: bool, result: Result<T, AlmightyError>) -> Result<Option<T>, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	match result {
+		Ok(value) => Ok(Some(value)),
+		Err(error) if gmod_path_mode => {
+			terminal_write(writer, format!("Warning: {error}\n\tContinuing anyway, since --gmod-path was used.\n").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			Ok(None)
+		},
+		Err(error) => Err(error)
+	}
+}
+
+async fn main_script_internal<W>(writer: fn() -> W, writer_is_interactive: bool, args: Args) -> Result<RunOutcome, AlmightyError>
+where
+	W: std::io::Write + 'static
+{
+	let now = Instant::now();
+	let dry_run = args.dry_run || args.command == Some(Command::Check);
+
+	// Only looks around, so it doesn't need anything below
+	if args.command == Some(Command::ListSteam) {
+		return list_steam_installations(writer, writer_is_interactive);
+	}
+
+	if let Some(Command::LaunchOptions { action }) = &args.command {
+		return edit_launch_options(writer, writer_is_interactive, &args, action).await;
+	}
+
+	// Abort if another instance is already running
+	let pid_path = extend_pathbuf_and_return(std::env::current_exe().unwrap().parent().unwrap().to_path_buf(), &["gmodpatchtool.pid"]);
+	let running_instance_pid = tokio::fs::read_to_string(&pid_path).await;
+	if let Ok(pid) = running_instance_pid {
+		if let Ok(pid) = pid.parse::<usize>() {
+			// Only that one process, instead of everything
+			let mut sys = System::new();
+			sys.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[sysinfo::Pid::from(pid)]), true);
+
+			if sys.process(sysinfo::Pid::from(pid)).is_some() {
+				return Err(AlmightyError::Generic(format!("Another instance of GModPatchTool is already running ({pid}).")));
+			}
+		}
+	}
+
+	// Create PID lockfile
+	let pid_write_result = tokio::fs::write(&pid_path, std::process::id().to_string()).await;
+	if let Err(error) = pid_write_result {
+		return Err(AlmightyError::Generic(format!("Failed to create gmodpatchtool.pid: {error}")));
+	}
+
+	// Serve everything from the offline bundle instead of the servers, if we have one
+	// It's kept alive until we're done, since unpacked archives are removed on drop
+	let offline_bundle = match &args.offline_bundle {
+		Some(bundle_path) => {
+			terminal_write(writer, format!("Using offline bundle: {}\n", bundle_path.display()).as_str(), true, None, MessageLevel::Info);
+			Some(bundle::OfflineBundle::open(bundle_path, &get_os_cache_dir()).map_err(AlmightyError::Generic)?)
+		},
+		None => None
+	};
+
+	// An offline bundle is the only mirror, since there's no internet to fall back to
+	let mirrors = match &offline_bundle {
+		Some(offline_bundle) => MirrorList::local(offline_bundle.dir.clone()),
+		None => {
+			let config = config::read_config(args.config.as_deref()).map_err(AlmightyError::Generic)?;
+
+			// Command line mirrors come first, then the config file's, then the built-in ones
+			let custom_roots: Vec<String> = args.mirror.iter().chain(config.mirrors.iter()).cloned().collect();
+			let use_default_mirrors = !args.no_default_mirrors && config.use_default_mirrors.unwrap_or(true);
+			let mirrors = MirrorList::new(&custom_roots, use_default_mirrors).map_err(AlmightyError::Generic)?;
+
+			if !custom_roots.is_empty() {
+				let mirrors_str = mirrors.binary.iter().map(|root| format!("\t{root}")).collect::<Vec<String>>().join("\n");
+				terminal_write(writer, format!("Using mirrors:\n{mirrors_str}\n").as_str(), true, None, MessageLevel::Info);
+			}
+
+			mirrors
+		}
+	};
+
+	// One client for everything, with the download limits shared between all files
+	let downloader = Arc::new(download::Downloader::new(args.max_parallel_downloads, args.limit_rate)?);
+
+	// Get local version
+	let local_version: u32 = env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap();
+
+	// Get remote version
+	terminal_write(writer, "Getting remote version...", true, None, MessageLevel::Info);
+
+	let remote_version_bytes = get_signed_file_bytes(writer, writer_is_interactive, &downloader, &mirrors.text, "version.txt").await?;
+
+	if remote_version_bytes.is_none() {
+		return Err(AlmightyError::Generic(if offline_bundle.is_some() { "Couldn't get remote version from the offline bundle!" } else { "Couldn't get remote version. Please check your internet connection!" }.to_string()));
+	}
+
+	let remote_version: u32 = String::from_utf8_lossy(&remote_version_bytes.unwrap())
+	.trim()
+	.parse()?;
+
+	if local_version >= remote_version {
+		terminal_write(writer, format!("You are running the latest version of GModPatchTool [Local: {local_version} / Remote: {remote_version}]!\n").as_str(), true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
+	} else {
+		terminal_write(writer, "WARNING: GModPatchTool is out of date! Please get the latest version at\nhttps://github.com/solsticegamestudios/GModPatchTool/releases", true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+
+		let mut secs_to_continue: u8 = 5;
+		while secs_to_continue > 0 {
+			terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			writer().flush().unwrap();
+			tokio::time::sleep(time::Duration::from_secs(1)).await;
+			secs_to_continue -= 1;
+		}
+
+		// Clear continuing line
+		if writer_is_interactive {
+			terminal_write(writer, "\x1B[0K\n", false, None, MessageLevel::Info);
+		}
+	}
+
+	// Warn/Exit if running as root/admin
+	#[cfg(windows)]
+	let root = is_elevated();
+
+	#[cfg(unix)]
+	let root = geteuid() == 0;
+
+	if root {
+		if args.run_as_root_with_security_risk {
+			terminal_write(writer, "WARNING: You are running GModPatchTool as root/with admin privileges. This may cause issues and is not typically necessary.", true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+
+			let mut secs_to_continue: u8 = 10;
+			while secs_to_continue > 0 {
+				terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+				writer().flush().unwrap();
+				tokio::time::sleep(time::Duration::from_secs(1)).await;
+				secs_to_continue -= 1;
+			}
+
+			// Clear continuing line
+			if writer_is_interactive {
+				terminal_write(writer, "\x1B[0K\n", false, None, MessageLevel::Info);
+			}
+		} else {
+			let elevated_msg = format!("You are running GModPatchTool as root/with admin privileges{}. This may cause issues and is not typically necessary.\n\nIF YOU KNOW WHAT YOU'RE DOING, you can allow this by running the tool with --run-as-root-with-security-risk. Aborting...", if cfg!(windows) { " (is User Account Control turned off?)" } else { "" });
+			return Err(AlmightyError::Generic(elevated_msg));
+		}
 	}
 
-	// HACK: Rip out the "WebStorage" section to mitigate stack overflow issues
-	// See `thread_stack_size` below
-	// See https://github.com/CosmicHorrorDev/vdf-rs/issues/54
-	let mut steam_user_localconfig_str = steam_user_localconfig_str.unwrap();
-	let webstorage_start_regex = Regex::new(r"WebStorage.+\s+\{").unwrap();
-	let webstorage_start_match = webstorage_start_regex.find(&steam_user_localconfig_str);
-
-	if let Some(webstorage_start_match) = webstorage_start_match {
-		let webstorage_open_bracket = webstorage_start_match.end();
+	// Abort if GMod is currently running
+	if is_gmod_running() {
+		return Err(AlmightyError::Generic("Garry's Mod is currently running. Please close it before running this tool.".to_string()));
+	}
 
-		let mut open_bracket_count: usize = 1;
-		let mut webstorage_close_bracket_offset: Option<usize> = None;
-		for (offset, char) in (0_usize..).zip(steam_user_localconfig_str[webstorage_open_bracket..].chars()) {
-			if char == '{' {
-				open_bracket_count += 1;
-			} else if char == '}' {
-				open_bracket_count -= 1;
-			}
+	// Warning for macOS users
+	#[cfg(target_os = "macos")]
+	{
+		terminal_write(writer, "WARNING: Garry's Mod is not well supported on macOS and may not be supported at all in the future:", true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+		terminal_write(writer, "\thttps://winteris.moe/share/2025-08-07_20-53-45.png", true, None, MessageLevel::Info);
+		terminal_write(writer, "\nOptions for playing the Windows version of GMod on macOS are located here:", true, if writer_is_interactive { Some("red") } else { None }, MessageLevel::Error);
+		terminal_write(writer, "\thttps://github.com/solsticegamestudios/GModPatchTool/issues/170", true, None, MessageLevel::Info);
 
-			if open_bracket_count == 0 {
-				webstorage_close_bracket_offset = Some(offset);
-				break;
-			}
+		let mut secs_to_continue: u8 = 10;
+		while secs_to_continue > 0 {
+			terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+			writer().flush().unwrap();
+			tokio::time::sleep(time::Duration::from_secs(1)).await;
+			secs_to_continue -= 1;
 		}
 
-		if let Some(webstorage_close_bracket_offset) = webstorage_close_bracket_offset {
-			let webstorage_close_bracket = webstorage_open_bracket + webstorage_close_bracket_offset;
-			steam_user_localconfig_str = format!("{}{}", &steam_user_localconfig_str[..webstorage_open_bracket], &steam_user_localconfig_str[webstorage_close_bracket..]);
+		// Clear continuing line
+		if writer_is_interactive {
+			terminal_write(writer, "\x1B[0K\n", false, None, MessageLevel::Info);
 		}
 	}
 
-	let steam_user_localconfig = vdf::from_str(steam_user_localconfig_str.as_str());
+	// Find GMod through Steam, unless --gmod-path says exactly where it is
+	let gmod_path_mode = args.gmod_path.is_some();
+	// With GMod's appmanifest, if Steam knows about it
+	let mut gmod_dirs: Vec<(PathBuf, String, Option<steam::SteamAppManifest>)> = vec![];
 
-	if let Err(error) = steam_user_localconfig {
-		return Err(AlmightyError::Generic(format!("Couldn't parse Steam localconfig.vdf. Is the file corrupt?\n\t{error}")));
-	}
+	// Steam Library GMod is in, for its Proton prefix
+	#[cfg_attr(not(target_os = "linux"), allow(unused_variables, unused_assignments, reason = "used on linux"))]
+	let gmod_library_path: Option<PathBuf>;
 
-	let steam_user_localconfig: SteamUserLocalConfig = steam_user_localconfig.unwrap();
-	let steam_user_localconfig_gmod = steam_user_localconfig.software.valve.steam.apps.gmod;
+	// Steam install and user, for the Steam-only checks below
+	let steam_user: Option<(SteamInstallation, SteamId)>;
 
-	if let Some(steam_user_localconfig_gmod) = steam_user_localconfig_gmod {
-		if let Some(steam_user_localconfig_gmod_launchopts) = &steam_user_localconfig_gmod.launch_options {
-			if steam_user_localconfig_gmod_launchopts.contains("-nochromium") {
-				terminal_write(writer, "WARNING: -nochromium is in GMod's Launch Options! CEF will not work with this.\n\tPlease go to Steam > Garry's Mod > Properties > General and remove it.\n\tAdditionally, if you have gmod-lua-menu installed, uninstall it.", true, if writer_is_interactive { Some("yellow") } else { None });
-
-				let mut secs_to_continue: u8 = 5;
-				while secs_to_continue > 0 {
-					terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None });
-					writer().flush().unwrap();
-					tokio::time::sleep(time::Duration::from_secs(1)).await;
-					secs_to_continue -= 1;
-				}
+	if let Some(gmod_path_arg) = &args.gmod_path {
+		// Make sure the path the user is forcing actually exists
+		let gmod_path = pathbuf_to_canonical_pathbuf(gmod_path_arg.clone(), true);
 
-				// Clear continuing line
-				if writer_is_interactive {
-					terminal_write(writer, "\x1B[0K\n", false, None);
-				}
-			}
+		if let Err(error) = gmod_path {
+			return Err(AlmightyError::Generic(format!("Please check the --gmod-path argument is pointing to a valid path:\n\t{error}")));
 		}
-	} else {
-		return Err(AlmightyError::Generic("Couldn't find Garry's Mod in user localconfig.vdf. Is Garry's Mod installed?".to_string()));
-	}
 
-	// Get remote manifest
-	terminal_write(writer, "Getting remote manifest...", true, None);
-
-	let remote_manifest_response = get_http_response(writer, writer_is_interactive, &TEXT_SERVER_ROOTS, "manifest.json").await;
-
-	if remote_manifest_response.is_none() {
-		terminal_write(writer, "", true, None); // Newline
-		return Err(AlmightyError::Generic("Couldn't get remote manifest. Please check your internet connection!".to_string()));
-	}
+		let gmod_path = gmod_path.unwrap();
 
-	let remote_manifest_response = remote_manifest_response.unwrap();
-	let remote_manifest = remote_manifest_response.json::<Manifest>()
-	.await?;
+		// <library>/steamapps/common/GarrysMod, if it's in a Steam Library at all
+		gmod_library_path = gmod_path.parent()
+			.filter(|common_path| common_path.ends_with("common"))
+			.and_then(Path::parent)
+			.and_then(Path::parent)
+			.map(Path::to_path_buf);
 
-	terminal_write(writer, "GModPatchTool Manifest Loaded!\n", true, None);
+		// clap makes sure --branch is set with --gmod-path
+		let gmod_branch = args.branch.clone().unwrap_or("public".to_string());
 
-	let platform_branches = remote_manifest.get(platform_masked);
-	if platform_branches.is_none() {
-		return Err(AlmightyError::Generic(format!("This operating system ({platform_masked}) is not supported!")));
-	}
+		terminal_write(writer, "Using --gmod-path: Steam is only checked if it's there, and any problems with it are just warnings.\n", true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
 
-	let platform_branch_files = platform_branches.unwrap().get(&gmod_branch);
-	if platform_branch_files.is_none() {
-		return Err(AlmightyError::Generic(format!("This Beta Branch of Garry's Mod ({gmod_branch}) is not supported! Please go to Steam > Garry's Mod > Properties > Betas, select the x86-64 beta, then try again.")));
-	}
+		let steam_installation = match &args.steam_install {
+			Some(steam_install_arg) => check_steam_result(writer, writer_is_interactive, true, get_steam_installation_arg(steam::find_installations(), steam_install_arg))?,
+			None => {
+				let steam_installations = steam::find_installations();
+				get_default_steam_installation_id(&steam_installations).and_then(|steam_installation_id| steam_installations.into_iter().nth(steam_installation_id))
+			}
+		};
 
-	let platform_branch_files = platform_branch_files.unwrap();
+		let mut gmod_app_manifest = None;
 
-	// Determine file integrity status
-	terminal_write(writer, "Determining file integrity status...", true, None);
+		steam_user = match steam_installation {
+			Some(steam_installation) => {
+				let steam_path_str = steam_installation.path.to_string_lossy();
 
-	// TODO: phf_map for these
-	let integrity_status_strings = HashMap::from([
-		(IntegrityStatus::NeedDelete, "Needs Delete"),
-		(IntegrityStatus::NeedOriginal, "Needs Original + Fix"),
-		(IntegrityStatus::NeedWipeFix, "Needs Wipe + Fix"),
-		(IntegrityStatus::NeedFix, "Needs Fix"),
-		(IntegrityStatus::Fixed, "Already Fixed")
-	]);
+				emit(writer, writer_is_interactive, Event::SteamPath { path: &steam_path_str });
 
-	#[allow(clippy::type_complexity)]
-	let integrity_results: Vec<(&String, Result<IntegrityStatus, String>, &IndexMap<String, String>)> = platform_branch_files.par_iter()
-	.map(|(filename, hashes)| {
-		let integrity_result;
-		if args.no_sourcescheme && filename.ends_with(".res") {
-			terminal_write(writer, format!("\t{filename}: Skipping due to --no-sourcescheme").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
-			integrity_result = Ok(IntegrityStatus::Fixed);
-		} else {
-			integrity_result = determine_file_integrity_status(gmod_path.clone(), filename, hashes);
-			let integrity_result_clone = integrity_result.clone();
+				// If Steam knows about this GMod directory, check its app state too
+				let gmod_install = steam_installation.find_gmod_installs().unwrap_or_default().into_iter().find(|gmod_install| gmod_install.find_install_dir().as_ref() == Some(&gmod_path));
 
-			match integrity_result_clone {
-				Ok(integrity_result_clone) => {
-					let integrity_status_string = integrity_status_strings[&integrity_result_clone];
-					terminal_write(writer, format!("\t{filename}: {integrity_status_string}").as_str(), true, None);
-				},
-				Err(error) => {
-					terminal_write(writer, format!("\t{filename}: {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
+				if let Some(gmod_install) = &gmod_install {
+					check_steam_result(writer, writer_is_interactive, true, check_gmod_app_state(writer, writer_is_interactive, &gmod_install.manifest))?;
+					check_gmod_build(writer, writer_is_interactive, &steam_installation, gmod_install, &gmod_branch);
 				}
-			}
-		}
 
-		(filename, integrity_result, hashes)
-	}).collect();
+				gmod_app_manifest = gmod_install.map(|gmod_install| gmod_install.manifest);
 
-	// Filter out fixed files, and if there were any i/o errors getting the hash, exit early
-	// We don't exit during the multithreaded iterator above because we want *all* of the failing files to list first
-	let mut pending_files: Vec<(&String, IntegrityStatus, &IndexMap<String, String>)> = vec![];
-	for (filename, result, hashes) in integrity_results {
-		match result {
-			Ok(result) => {
-				if result != IntegrityStatus::Fixed {
-					pending_files.push((filename, result, hashes));
-				}
+				let gmod_last_owner = gmod_app_manifest.as_ref().map(|gmod_app_manifest| gmod_app_manifest.last_owner).unwrap_or_default();
+				let steam_id = check_steam_result(writer, writer_is_interactive, true, get_steam_user(writer, writer_is_interactive, &steam_installation, args.steam_user.as_deref(), gmod_last_owner))?;
+
+				steam_id.map(|steam_id| (steam_installation, steam_id))
 			},
-			Err(_) => {
-				return Err(AlmightyError::Generic("Failed to get integrity status of one or more files!".to_string()));
+			None => {
+				terminal_write(writer, "Warning: Couldn't find Steam, so the Steam User, Launch Options, and App State checks are skipped.\n", true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+				None
 			}
-		}
-	}
+		};
 
-	let pending_files_len = pending_files.len();
-	if pending_files_len > 0 {
-		// Figure out where our cache should go based on OS
-		let os_cache_dir = if let Some(dirs_cache_dir) = dirs::cache_dir() { dirs_cache_dir } else { std::env::temp_dir() };
+		emit(writer, writer_is_interactive, Event::GmodBranch { branch: &gmod_branch });
 
-		// Delete old GModCEFCodecFix cache directory
-		#[cfg(windows)]
-		let old_cache_dir = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(os_cache_dir.clone(), &["Temp", "GModCEFCodecFix"]), false);
+		gmod_dirs.push((gmod_path, gmod_branch, gmod_app_manifest));
+	} else {
+		// Find Steam
+		let steam_installations = steam::find_installations();
+		let steam_installation = match &args.steam_install {
+			Some(steam_install_arg) => Some(get_steam_installation_arg(steam_installations, steam_install_arg)?),
+			None => {
+				let default_steam_installation_id = get_default_steam_installation_id(&steam_installations);
+
+				// Let them know which one we picked, and how to pick a different one
+				// Only worth stopping for if we can't tell which one they use
+				if steam_installations.len() > 1 {
+					let gmod_installation_count = steam_installations.iter().filter(|steam_installation| steam_installation.has_gmod()).count();
+					let ambiguous = gmod_installation_count != 1;
+
+					let mut steam_installations_str: String = "".to_string();
+					for (steam_installation_id, steam_installation) in steam_installations.iter().enumerate() {
+						steam_installations_str += format!("\n\t{}. {}", steam_installation_id + 1, steam_installation.path.to_string_lossy()).as_str();
+
+						if Some(steam_installation_id) == default_steam_installation_id {
+							steam_installations_str += " [Using This One]";
+						}
+					}
 
-		#[cfg(not(windows))]
-		let old_cache_dir = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(os_cache_dir.clone(), &["GModCEFCodecFix"]), false);
+					terminal_write(writer, format!("{}Multiple Steam Installations Detected!{}{steam_installations_str}\n\tTo use a different one, run the tool with --steam-install <number or path> (see list-steam for details).", if ambiguous { "Warning: " } else { "" }, if ambiguous { " This may cause issues:" } else { "" }).as_str(), true, if writer_is_interactive && ambiguous { Some("yellow") } else { None }, if ambiguous { MessageLevel::Warning } else { MessageLevel::Info });
 
-		if let Ok(old_cache_dir) = old_cache_dir {
-			let old_cache_dir_result = tokio::fs::remove_dir_all(old_cache_dir).await;
+					if ambiguous {
+						let mut secs_to_continue: u8 = 5;
+						while secs_to_continue > 0 {
+							terminal_write(writer, format!("\tContinuing in {secs_to_continue} second(s)...\r").as_str(), false, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+							writer().flush().unwrap();
+							tokio::time::sleep(time::Duration::from_secs(1)).await;
+							secs_to_continue -= 1;
+						}
 
-			match old_cache_dir_result {
-				Ok(_) => {
-					terminal_write(writer,"Successfully removed old GModCEFCodecFix cache directory.", true, None);
-				},
-				Err(error) => {
-					terminal_write(writer, format!("Failed to remove old GModCEFCodecFix cache directory: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
+						// Clear continuing line
+						if writer_is_interactive {
+							terminal_write(writer, "\x1B[0K\n", false, None, MessageLevel::Info);
+						}
+					} else {
+						terminal_write(writer, "", true, None, MessageLevel::Info); // Newline
+					}
 				}
+
+				default_steam_installation_id.and_then(|steam_installation_id| steam_installations.into_iter().nth(steam_installation_id))
 			}
+		};
+
+		if steam_installation.is_none() {
+			return Err(AlmightyError::Generic("Couldn't find Steam. If it's installed, try using the --steam-install argument to force a specific path.".to_string()));
 		}
 
-		// Create new GModPatchTool cache directory if it doesn't exist
-		let cache_path = extend_pathbuf_and_return(os_cache_dir, &["GModPatchTool"]);
-		let mut cache_path_str = cache_path.to_string_lossy();
-		let mut cache_dir = pathbuf_to_canonical_pathbuf(cache_path.clone(), false);
+		let steam_installation = steam_installation.unwrap();
+		let steam_path_str = steam_installation.path.to_string_lossy();
 
-		// ...but make sure it doesn't exist (and clear it) if disable_cache is set
-		if args.disable_cache {
-			if let Ok(cache_dir) = cache_dir {
-				let remove_result = tokio::fs::remove_dir_all(cache_dir).await;
+		emit(writer, writer_is_interactive, Event::SteamPath { path: &steam_path_str });
 
-				match remove_result {
-					Ok(_) => {
-						terminal_write(writer,"\n[disable-cache:Pre] Successfully cleared GModPatchTool cache directory.", true, None);
-					},
-					Err(error) => {
-						terminal_write(writer, format!("\n[disable-cache:Pre] Failed to clear GModPatchTool cache directory: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
-					}
-				}
-			}
+		// Get GMod Steam Libraries and Manifests
+		let gmod_installs = steam_installation.find_gmod_installs().map_err(AlmightyError::Generic)?;
 
-			cache_dir = pathbuf_to_canonical_pathbuf(cache_path.clone(), false);
+		if gmod_installs.is_empty() {
+			return Err(AlmightyError::Generic("Couldn't find GMod's appmanifest_4000.acf. Is Garry's Mod installed?".to_string()));
 		}
 
-		if cache_dir.is_err() {
-			let create_result = tokio::fs::create_dir(cache_path.clone()).await;
+		let gmod_installs = select_gmod_installs(writer, writer_is_interactive, gmod_installs, &args.gmod_install)?;
 
-			if create_result.is_ok() {
-				cache_dir = pathbuf_to_canonical_pathbuf(cache_path.clone(), false);
-			}
+		let steam_id = get_steam_user(writer, writer_is_interactive, &steam_installation, args.steam_user.as_deref(), gmod_installs[0].manifest.last_owner)?;
+
+		let mut gmod_install_error = None;
+		for gmod_install in &gmod_installs {
+			let (gmod_path, gmod_branch) = match get_gmod_install_dir(writer, writer_is_interactive, gmod_install) {
+				Ok(gmod_install_dir) => gmod_install_dir,
+				// With --gmod-install all, a leftover copy that isn't usable shouldn't stop the others from being patched
+				Err(error) if gmod_installs.len() > 1 => {
+					terminal_write(writer, format!("Skipping Garry's Mod in {}: {error}", gmod_install.library_path.display()).as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
+					gmod_install_error = Some(error);
+					continue;
+				},
+				Err(error) => return Err(error)
+			};
+
+			check_gmod_build(writer, writer_is_interactive, &steam_installation, gmod_install, &gmod_branch);
+
+			gmod_dirs.push((gmod_path, gmod_branch, Some(gmod_install.manifest.clone())));
 		}
 
-		// Can't access or create the cache directory!
-		if let Err(error) = cache_dir {
-			return Err(AlmightyError::Generic(format!("Failed to create cache directory ({error}):\n\t{cache_path_str}")));
+		if gmod_dirs.is_empty() && let Some(error) = gmod_install_error {
+			return Err(error);
 		}
 
-		let cache_dir = cache_dir.unwrap();
-		cache_path_str = cache_dir.to_string_lossy();
+		// With --gmod-install all, the one Steam actually launches
+		gmod_library_path = gmod_installs.iter().find(|gmod_install| gmod_install.listed_by_steam).unwrap_or(&gmod_installs[0]).library_path.clone().into();
 
-		terminal_write(writer, format!("\nGModPatchTool Cache Directory: {cache_path_str}\n").as_str(), true, None);
+		steam_user = Some((steam_installation, steam_id));
+	}
 
-		// Download what we need
-		terminal_write(writer, "Downloading patch files...", true, None);
+	// Determine target platform
+	// Get GMod CompatTool config (Steam Linux Runtime, Proton, etc) on Linux
+	// NOTE: platform_masked is specifically for Proton
+	let platform = if cfg!(windows) { "windows" } else if cfg!(target_os = "macos") { "macos" } else { "linux" };
 
-		let mut download_futures = JoinSet::new();
-		for (filename, integrity_status, hashes) in &pending_files {
-			// Need Original
-			if *integrity_status == IntegrityStatus::NeedOriginal {
-				download_futures.spawn(download_file_to_cache(writer, writer_is_interactive, cache_dir.clone(), format!("originals/{platform_masked}/{gmod_branch}/{filename}.zst"), hashes["original"].clone()));
-			}
+	let mut platform_masked = platform;
 
-			// Need Fix (we filtered out IntegrityStatus::Fixed above, but we still need IntegrityStatus::NeedDelete for later)
-			if *integrity_status != IntegrityStatus::NeedDelete {
-				download_futures.spawn(download_file_to_cache(writer, writer_is_interactive, cache_dir.clone(), format!("patches/{platform_masked}/{gmod_branch}/{filename}.bsdiff"), hashes["patch"].clone()));
-			}
-		}
+	#[cfg_attr(not(target_os = "linux"), expect(unused_mut, reason = "used on linux"))]
+	let mut gmod_compattool = "none".to_string();
 
-		while let Some(download_result) = download_futures.join_next().await {
-			if download_result.is_err() {
-				return Err(AlmightyError::Generic("Failed to download one or more patch files!".to_string()));
-			}
+	#[cfg(target_os = "linux")]
+	if let Some((steam_installation, _)) = &steam_user && let Some(Some(compattool)) = check_steam_result(writer, writer_is_interactive, gmod_path_mode, steam_installation.read_compat_tool(steam::GMOD_APP_ID).map_err(AlmightyError::Generic))? {
+		if compattool.contains("proton") {
+			platform_masked = "windows";
 		}
 
-		// Patch the files
-		terminal_write(writer, format!("\nPatching {pending_files_len} file(s)...").as_str(), true, None);
+		gmod_compattool = compattool;
+	}
 
-		// TODO: Early exit if any patches fail
-		let patch_results: Vec<(&String, IntegrityStatus)> = pending_files.par_iter()
-		.map(|(filename, integrity_status, hashes)| {
-			let new_integrity_status = patch_file(
-				writer,
-				writer_is_interactive,
-				&integrity_status_strings,
-				&gmod_path,
-				platform_masked,
-				&gmod_branch,
-				&cache_dir,
-				filename,
-				integrity_status,
-				hashes
-			);
+	// Steam Play's default for all other titles never applies to GMod, since it has a native Linux build
+	#[cfg(target_os = "linux")]
+	if gmod_compattool == "none" && let Some((steam_installation, _)) = &steam_user && let Ok(Some(global_compattool)) = steam_installation.read_global_compat_tool() {
+		terminal_write(writer, format!("Note: Steam Play is enabled for all other titles ({global_compattool}), but Garry's Mod has a native Linux version, so Steam doesn't run it through that. To use Proton, set it in Steam > Garry's Mod > Properties > Compatibility.\n").as_str(), true, None, MessageLevel::Info);
+	}
 
-			(*filename, new_integrity_status)
-		}).collect();
+	// Only set with --gmod-path, where Steam may not be there to tell us
+	if let Some(platform_arg) = &args.platform {
+		platform_masked = platform_arg.as_str();
+	}
 
-		for (_, integrity_status) in patch_results {
-			if integrity_status != IntegrityStatus::Fixed {
-				return Err(AlmightyError::Generic("Failed to patch one or more files!".to_string()));
-			}
-		}
+	emit(writer, writer_is_interactive, Event::TargetPlatform { platform, platform_masked, compat_tool: &gmod_compattool });
 
-		if args.disable_cache {
-			let remove_result = tokio::fs::remove_dir_all(cache_dir).await;
+	// Repeated at the end, so they aren't lost among the file output
+	let mut run_warnings: Vec<String> = vec![];
 
-			match remove_result {
-				Ok(_) => {
-					terminal_write(writer,"\n[disable-cache:Post] Successfully cleared GModPatchTool cache directory.", true, None);
-				},
-				Err(error) => {
-					terminal_write(writer, format!("\n[disable-cache:Post] Failed to clear GModPatchTool cache directory: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
-				}
-			}
-		}
-	} else {
-		terminal_write(writer, "No files need patching!", true, None);
+	#[cfg(target_os = "linux")]
+	if let Some((steam_installation, _)) = &steam_user && gmod_compattool.contains("proton") {
+		check_proton(writer, writer_is_interactive, steam_installation, &gmod_compattool, gmod_library_path.as_deref(), &mut run_warnings);
 	}
 
-	// Make sure executables are executable on Linux and macOS
-	// TODO: Windows support...but at the time of writing it's not well supported in Rust
-	// This is done separately because we want it to apply to ALL files regardless of if they needed to be patched
-	// https://github.com/solsticegamestudios/GModPatchTool/issues/161
-	#[cfg(unix)]
-	{
-		terminal_write(writer, "\nApplying file permissions...", true, None);
+	#[cfg_attr(not(target_os = "linux"), expect(unused_variables, reason = "used on linux"))]
+	let gmod_launch_options = match &steam_user {
+		Some((steam_installation, steam_id)) => check_steam_result(writer, writer_is_interactive, gmod_path_mode, check_launch_options(writer, writer_is_interactive, &downloader, &mirrors.text, &steam_installation.path, steam_id, platform_masked, &gmod_compattool, &mut run_warnings).await)?.flatten(),
+		None => None
+	};
 
-		for (filename, fileinfo) in platform_branch_files {
-			let executable = fileinfo.get("executable");
+	// Container native GMod runs in, if any
+	#[cfg_attr(not(target_os = "linux"), expect(unused_mut, reason = "used on linux"))]
+	let mut gmod_container: Option<&str> = None;
 
-			if let Some(executable) = executable {
-				if executable == "true" {
-					let gmod_file_parts: Vec<&str> = filename.split("/").collect();
-					let gmod_file_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_path.clone(), &gmod_file_parts[..]), true);
+	#[cfg(target_os = "linux")]
+	if let Some((steam_installation, _)) = &steam_user && let Some(linux_runtime) = steam::LinuxRuntime::from_compat_tool_name(&gmod_compattool) {
+		gmod_container = Some(check_linux_runtime(writer, writer_is_interactive, steam_installation, linux_runtime, &gmod_compattool, gmod_launch_options.as_ref(), &mut run_warnings));
+	}
 
-					if let Ok(gmod_file_path) = gmod_file_path {
-						let metadata = tokio::fs::metadata(&gmod_file_path).await;
+	// Get remote manifest
+	terminal_write(writer, "Getting remote manifest...", true, None, MessageLevel::Info);
 
-						match metadata {
-							Ok(metadata) => {
-								// Ensure the executable bit is present and apply it to the file
-								let mut perms = metadata.permissions();
-								perms.set_mode(perms.mode() | 0o111);
-								let perms_result: Result<(), io::Error> = tokio::fs::set_permissions(&gmod_file_path, perms).await;
+	let remote_manifest_bytes = get_signed_file_bytes(writer, writer_is_interactive, &downloader, &mirrors.text, "manifest.json").await?;
 
-								match perms_result {
-									Ok(_) => {
-										terminal_write(writer, format!("\t{filename}").as_str(), true, None);
-									},
-									Err(error) => {
-										terminal_write(writer, format!("\tFailed to Apply Permissions: {filename} | {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
-										// TODO: Fatal?
-									}
-								}
-							},
-							Err(error) => {
-								terminal_write(writer, format!("\tFailed to Apply Permissions: {filename} | {error}").as_str(), true, if writer_is_interactive { Some("red") } else { None });
-								// TODO: Fatal?
-							}
-						}
-					}
-				}
-			}
-		}
+	if remote_manifest_bytes.is_none() {
+		terminal_write(writer, "", true, None, MessageLevel::Info); // Newline
+		return Err(AlmightyError::Generic(if offline_bundle.is_some() { "Couldn't get remote manifest from the offline bundle!" } else { "Couldn't get remote manifest. Please check your internet connection!" }.to_string()));
 	}
 
-	// Delete ChromiumCache/ChromiumCacheMultirun
-	// Solves issues with being corrupt/stuck lockfiles, and GMod MUST NOT be running for this tool to run, so it probably solves more issues than it could create
-	if !args.skip_clear_chromiumcache {
-		let gmod_chromiumcache_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_path.clone(), &["ChromiumCache"]), false);
-		if let Ok(gmod_chromiumcache_path) = gmod_chromiumcache_path {
-			terminal_write(writer, "\nClearing ChromiumCache...", true, None);
-			if let Err(error) = tokio::fs::remove_dir_all(gmod_chromiumcache_path).await {
-				terminal_write(writer, format!("\tFailed: {error}\n\tYou may want to delete ChromiumCache from the GarrysMod directory manually!").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
-			} else {
-				terminal_write(writer, "Done!", true, None);
-			}
-		}
+	let remote_manifest: Manifest = serde_json::from_slice(&remote_manifest_bytes.unwrap())?;
 
-		let gmod_chromiumcachemultirun_path = pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(gmod_path.clone(), &["ChromiumCacheMultirun"]), false);
-		if let Ok(gmod_chromiumcachemultirun_path) = gmod_chromiumcachemultirun_path {
-			terminal_write(writer, "\nClearing ChromiumCacheMultirun...", true, None);
-			if let Err(error) = tokio::fs::remove_dir_all(gmod_chromiumcachemultirun_path).await {
-				terminal_write(writer, format!("\tFailed: {error}\n\tYou may want to delete ChromiumCacheMultirun from the GarrysMod directory manually!").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
-			} else {
-				terminal_write(writer, "Done!", true, None);
-			}
-		}
+	terminal_write(writer, "GModPatchTool Manifest Loaded!\n", true, None, MessageLevel::Info);
+
+	let remote_builds = get_manifest_builds(writer, writer_is_interactive, &downloader, &mirrors.text).await;
+
+	let mut outcome = RunOutcome::Patched;
+	for (gmod_path, gmod_branch, gmod_app_manifest) in &gmod_dirs {
+		check_manifest_build(writer, writer_is_interactive, &args, remote_builds.as_ref(), platform_masked, gmod_branch, gmod_app_manifest.as_ref(), dry_run, &mut run_warnings)?;
+
+		let gmod_outcome = patch_gmod_dir(writer, writer_is_interactive, &args, &downloader, &mirrors, offline_bundle.as_ref(), &remote_manifest, gmod_path, gmod_branch, platform_masked, root, dry_run, now).await?;
+
+		// Report the least finished outcome of all of them
+		outcome = match (outcome, gmod_outcome) {
+			(RunOutcome::ChangesNeeded, _) | (_, RunOutcome::ChangesNeeded) => RunOutcome::ChangesNeeded,
+			(RunOutcome::Patched, RunOutcome::Patched) => RunOutcome::Patched,
+			_ => RunOutcome::Done
+		};
 	}
 
-	// TODO: Update BASS? https://github.com/Facepunch/garrysmod-requests/issues/1885
-	// TODO: Check dxlevel/d3d9ex support in Proton, and if there's anything we can do about it
+	emit(writer, writer_is_interactive, Event::RunSummary { platform_masked, compat_tool: &gmod_compattool, container: gmod_container, warnings: &run_warnings });
 
-	let now = now.elapsed().as_secs_f64();
-	terminal_write(writer, format!("\nGModPatchTool applied successfully! Took {now} second(s).").as_str(), true, if writer_is_interactive { Some("green") } else { None });
+	// Restores, rollbacks, and dry runs are done here
+	if outcome != RunOutcome::Patched {
+		return Ok(outcome);
+	}
 
 	if args.launch_gmod {
-		terminal_write(writer, "Launching Garry's Mod...", true, if writer_is_interactive { Some("green") } else { None });
+		terminal_write(writer, "Launching Garry's Mod...", true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
 
 		let open_result = open::that("steam://rungameid/4000");
 		if let Err(error) = open_result {
-			terminal_write(writer, format!("\tFailed: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None });
+			terminal_write(writer, format!("\tFailed: {error}").as_str(), true, if writer_is_interactive { Some("yellow") } else { None }, MessageLevel::Warning);
 		}
 	} else {
-		terminal_write(writer, "You can now launch Garry's Mod in Steam.", true, if writer_is_interactive { Some("green") } else { None });
+		terminal_write(writer, "You can now launch Garry's Mod in Steam.", true, if writer_is_interactive { Some("green") } else { None }, MessageLevel::Success);
 	}
 
-	terminal_write(writer, "\n💖 Did you find this tool useful? Please consider donating a few dollars to help support it:", true, if writer_is_interactive { Some("magenta") } else { None });
-	terminal_write(writer, "\thttps://www.solsticegamestudios.com/donate/", true, None);
+	terminal_write(writer, "\n💖 Did you find this tool useful? Please consider donating a few dollars to help support it:", true, if writer_is_interactive { Some("magenta") } else { None }, MessageLevel::Info);
+	terminal_write(writer, "\thttps://www.solsticegamestudios.com/donate/", true, None, MessageLevel::Info);
 
-	Ok(())
+	Ok(RunOutcome::Done)
 }
 
 fn terminal_exit_handler() {
@@ -1510,7 +3299,7 @@ fn terminal_exit_handler() {
 	}
 }
 
-fn main_script<W>(writer: fn() -> W, writer_is_interactive: bool, args: Args) -> Result<(), AlmightyError>
+fn main_script<W>(writer: fn() -> W, writer_is_interactive: bool, args: Args) -> Result<RunOutcome, AlmightyError>
 where
 	W: std::io::Write + 'static
 {
@@ -1520,9 +3309,6 @@ where
 
 	tokio::runtime::Builder::new_multi_thread()
 		.enable_all()
-		// HACK: Default is typically 2 MiB, but Vdf parsing can sometimes overflow the stack...
-		// TODO: Report localconfig.vdf/config.vdf overflow: https://github.com/CosmicHorrorDev/vdf-rs/issues
-		.thread_stack_size(0x800000) // 8 MiB
 		.build()
 		.map_err(|error| AlmightyError::Generic(format!("Failed to create Tokio runtime: {error}")))?
 		.block_on(
@@ -1584,7 +3370,10 @@ pub fn main() {
 			}
 		}
 
-		if force_gui.unwrap_or(!is_terminal || !is_ansi) {
+		// --output json is for launchers/scripts reading our stdout, which is never a terminal for them
+		let output_json = Args::try_parse().is_ok_and(|args| args.output == OutputFormat::Json);
+
+		if !output_json && force_gui.unwrap_or(!is_terminal || !is_ansi) {
 			// TODO: Make this safe if possible
 			// https://doc.rust-lang.org/std/env/fn.set_var.html
 			unsafe {
@@ -1600,33 +3389,62 @@ pub fn main() {
 		}
 	}
 
-	if is_ansi {
-		print!("\x1B]0;GModPatchTool\x07");
-	}
-
 	let writer = std::io::stdout;
-	let writer_is_interactive = is_terminal;
-
-	// Write about
-	terminal_write(writer, ABOUT, true, if writer_is_interactive { Some("cyan") } else { None });
 
 	// Parse the args
 	let args = match Args::try_parse() {
 		Ok(args) => args,
 		Err(error) => {
+			if is_ansi {
+				print!("\x1B]0;GModPatchTool\x07");
+			}
+
+			terminal_write(writer, ABOUT, true, if is_terminal { Some("cyan") } else { None }, MessageLevel::Info);
+
 			let _ = error.print();
 			terminal_exit_handler();
 			std::process::exit(error.exit_code());
 		},
 	};
 
-	let skip_exit_prompt = args.skip_exit_prompt;
+	OUTPUT_FORMAT.set(args.output).unwrap();
+
+	let writer_is_interactive = is_terminal;
+
+	if is_ansi && args.output == OutputFormat::Text {
+		print!("\x1B]0;GModPatchTool\x07");
+	}
 
-	if let Err(error) = main_script(writer, writer_is_interactive, args) {
-		error!("{error}");
+	// Write about
+	if args.output == OutputFormat::Text {
+		terminal_write(writer, ABOUT, true, if writer_is_interactive { Some("cyan") } else { None }, MessageLevel::Info);
 	}
 
+	let skip_exit_prompt = args.skip_exit_prompt;
+
+	let exit_code = match main_script(writer, writer_is_interactive, args) {
+		Ok(outcome) => {
+			let (outcome_str, exit_code) = match outcome {
+				RunOutcome::Done | RunOutcome::Patched => ("done", 0),
+				RunOutcome::ChangesNeeded => ("changes_needed", EXIT_CODE_CHANGES_NEEDED)
+			};
+
+			emit(writer, writer_is_interactive, Event::Outcome { outcome: outcome_str, error: None });
+			exit_code
+		},
+		Err(error) => {
+			if output_format() == OutputFormat::Text {
+				error!("{error}");
+			}
+
+			emit(writer, writer_is_interactive, Event::Outcome { outcome: "error", error: Some(error.to_string()) });
+			EXIT_CODE_ERROR
+		}
+	};
+
 	if is_terminal && !skip_exit_prompt {
 		terminal_exit_handler();
 	}
+
+	std::process::exit(exit_code);
 }
@@ -0,0 +1,127 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// How often to report progress for a single download
+pub const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct ProgressSnapshot {
+	pub transferred: u64,
+	// None if we don't know the size yet
+	pub size: Option<u64>,
+	pub bytes_per_sec: u64,
+	pub eta_secs: Option<u64>
+}
+
+impl ProgressSnapshot {
+	fn new(transferred: u64, size: Option<u64>, session_transferred: u64, elapsed: Duration) -> ProgressSnapshot {
+		let elapsed_secs = elapsed.as_secs_f64();
+		let bytes_per_sec = if elapsed_secs > 0.0 { (session_transferred as f64 / elapsed_secs) as u64 } else { 0 };
+		let eta_secs = match size {
+			Some(size) if bytes_per_sec > 0 => Some(size.saturating_sub(transferred) / bytes_per_sec),
+			_ => None
+		};
+
+		ProgressSnapshot {
+			transferred,
+			size,
+			bytes_per_sec,
+			eta_secs
+		}
+	}
+
+	pub fn describe(&self) -> String {
+		let transferred_mib = self.transferred as f64 / 0x100000 as f64;
+		let mib_per_sec = self.bytes_per_sec as f64 / 0x100000 as f64;
+
+		let mut description = match self.size {
+			Some(size) => format!("{transferred_mib:.2}/{:.2} MiB", size as f64 / 0x100000 as f64),
+			None => format!("{transferred_mib:.2} MiB")
+		};
+
+		description += format!(" @ {mib_per_sec:.2} MiB/s").as_str();
+
+		if let Some(eta_secs) = self.eta_secs {
+			description += format!(", ETA {}:{:02}", eta_secs / 60, eta_secs % 60).as_str();
+		}
+
+		description
+	}
+}
+
+// Progress of a single download
+// Bytes that were already there when we resumed count towards the progress, but not the speed
+pub struct FileProgress {
+	start: Instant,
+	last_report: Instant,
+	start_transferred: u64,
+	transferred: u64,
+	size: Option<u64>
+}
+
+impl FileProgress {
+	pub fn new(start_transferred: u64, size: Option<u64>) -> FileProgress {
+		let now = Instant::now();
+
+		FileProgress {
+			start: now,
+			last_report: now,
+			start_transferred,
+			transferred: start_transferred,
+			size
+		}
+	}
+
+	// Returns true when it's time to report progress again
+	pub fn add(&mut self, bytes: u64) -> bool {
+		self.transferred += bytes;
+
+		if self.last_report.elapsed() >= PROGRESS_REPORT_INTERVAL {
+			self.last_report = Instant::now();
+			true
+		} else {
+			false
+		}
+	}
+
+	pub fn snapshot(&self) -> ProgressSnapshot {
+		ProgressSnapshot::new(self.transferred, self.size, self.transferred - self.start_transferred, self.start.elapsed())
+	}
+}
+
+// Progress of all of the downloads in a batch, shared between the download tasks
+pub struct TotalProgress {
+	start: Instant,
+	// (expected, transferred)
+	bytes: Mutex<(u64, u64)>
+}
+
+impl TotalProgress {
+	pub fn new(expected: u64) -> TotalProgress {
+		TotalProgress {
+			start: Instant::now(),
+			bytes: Mutex::new((expected, 0))
+		}
+	}
+
+	pub fn add_expected(&self, bytes: u64) {
+		self.bytes.lock().unwrap().0 += bytes;
+	}
+
+	// For files that were cached, resumed, or failed
+	pub fn remove_expected(&self, bytes: u64) {
+		let mut total_bytes = self.bytes.lock().unwrap();
+		total_bytes.0 = total_bytes.0.saturating_sub(bytes);
+	}
+
+	pub fn add_transferred(&self, bytes: u64) {
+		self.bytes.lock().unwrap().1 += bytes;
+	}
+
+	pub fn snapshot(&self) -> ProgressSnapshot {
+		let (expected, transferred) = *self.bytes.lock().unwrap();
+
+		ProgressSnapshot::new(transferred, Some(expected.max(transferred)), transferred, self.start.elapsed())
+	}
+}
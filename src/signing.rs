@@ -0,0 +1,97 @@
+#[cfg(feature = "generate")]
+use ed25519_dalek::{Signer, SigningKey};
+
+#[cfg(feature = "patch")]
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+#[cfg(feature = "patch")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "patch")]
+use indexmap::IndexMap;
+
+// manifest.json and version.txt get a detached signature next to them (e.g. manifest.json.sig)
+// The manifest has the blake3 hashes of every patch/original, so signing it covers everything we download
+// .sig files are two lines: the hex-encoded signature, then when it was signed (unix time)
+// The signature covers "<signed at>\n" followed by the file, so the time can't be changed without breaking it
+pub const SIGNATURE_EXTENSION: &str = ".sig";
+
+// Public half of the files branch signing key (generate prints it when run with --signing-key)
+// GMODPATCHTOOL_PUBLIC_KEY at build time overrides it, for testing against mirrors signed with another key
+#[cfg(feature = "patch")]
+const PUBLIC_KEY: &str = match option_env!("GMODPATCHTOOL_PUBLIC_KEY") {
+	Some(public_key) => public_key,
+	None => "51d087ea0f57eef2dc41e30de5e6a82b8b212b43a7dd2095dc258349f999b858"
+};
+
+#[cfg(feature = "patch")]
+const SIGNED_AT_FILENAME: &str = "signed_at.json";
+
+fn decode_hex_array<const N: usize>(hex_str: &str, name: &str) -> Result<[u8; N], String> {
+	let bytes = hex::decode(hex_str.trim()).map_err(|error| format!("Invalid {name}: {error}"))?;
+
+	bytes.try_into().map_err(|_| format!("Invalid {name}: Expected {N} bytes"))
+}
+
+fn get_signed_message(contents: &[u8], signed_at: u64) -> Vec<u8> {
+	let mut message = format!("{signed_at}\n").into_bytes();
+	message.extend_from_slice(contents);
+
+	message
+}
+
+// Signing keys are the hex-encoded 32 byte secret (e.g. from `openssl rand -hex 32`)
+#[cfg(feature = "generate")]
+pub fn read_signing_key(signing_key_hex: &str) -> Result<SigningKey, String> {
+	Ok(SigningKey::from_bytes(&decode_hex_array(signing_key_hex, "signing key")?))
+}
+
+#[cfg(feature = "generate")]
+pub fn get_public_key_hex(signing_key: &SigningKey) -> String {
+	hex::encode(signing_key.verifying_key().to_bytes())
+}
+
+// Returns the contents of the .sig file
+#[cfg(feature = "generate")]
+pub fn sign(signing_key: &SigningKey, contents: &[u8], signed_at: u64) -> String {
+	let signature = hex::encode(signing_key.sign(&get_signed_message(contents, signed_at)).to_bytes());
+
+	format!("{signature}\n{signed_at}\n")
+}
+
+// Returns when it was signed
+#[cfg(feature = "patch")]
+pub fn verify(contents: &[u8], signature_file: &str) -> Result<u64, String> {
+	let mut signature_lines = signature_file.lines();
+	let signature_hex = signature_lines.next().unwrap_or_default();
+	let signed_at = signature_lines.next().and_then(|signed_at| signed_at.trim().parse().ok()).ok_or("Missing signing time")?;
+
+	let public_key = VerifyingKey::from_bytes(&decode_hex_array(PUBLIC_KEY, "public key")?).map_err(|error| error.to_string())?;
+	let signature = Signature::from_bytes(&decode_hex_array(signature_hex, "signature")?);
+
+	public_key.verify(&get_signed_message(contents, signed_at), &signature).map_err(|_| "Signature doesn't match".to_string())?;
+
+	Ok(signed_at)
+}
+
+// Newest signing time we've accepted for each signed file, so a mirror can't hand us an older (but validly signed) one again
+#[cfg(feature = "patch")]
+pub fn get_signed_at_path() -> Option<PathBuf> {
+	dirs::data_local_dir().map(|data_dir| data_dir.join("GModPatchTool").join(SIGNED_AT_FILENAME))
+}
+
+#[cfg(feature = "patch")]
+pub fn read_signed_at(signed_at_path: &Path) -> IndexMap<String, u64> {
+	std::fs::read(signed_at_path).ok()
+		.and_then(|signed_at_bytes| serde_json::from_slice(&signed_at_bytes).ok())
+		.unwrap_or_default()
+}
+
+#[cfg(feature = "patch")]
+pub fn write_signed_at(signed_at_path: &Path, signed_at: &IndexMap<String, u64>) -> Result<(), String> {
+	if let Some(signed_at_dir) = signed_at_path.parent() {
+		std::fs::create_dir_all(signed_at_dir).map_err(|error| error.to_string())?;
+	}
+
+	std::fs::write(signed_at_path, serde_json::to_vec(signed_at).map_err(|error| error.to_string())?).map_err(|error| error.to_string())
+}
@@ -0,0 +1,811 @@
+use crate::*;
+use serde::Deserialize;
+
+use super::vdf;
+
+// VDF structs
+//
+// Steam/config/loginusers.vdf
+//
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamLoginUser {
+	#[serde(alias = "accountname")]
+	account_name: String,
+	#[serde(alias = "personaname")]
+	persona_name: String,
+	//remember_password: bool,
+	//wants_offline_mode: bool,
+	//skip_offline_mode_warning: bool,
+	//allow_auto_login: bool,
+	#[serde(alias = "mostrecent", default)]
+	most_recent: bool,
+	#[serde(alias = "timestamp", default)]
+	timestamp: u64 // Y2K38
+}
+
+//
+// Steam/config/libraryfolders.vdf
+//
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamLibraryFolder {
+	#[serde(alias = "path")]
+	path: String,
+	//label: String,
+	//contentid: i64,
+	//totalsize: u64,
+	//update_clean_bytes_tally: u64,
+	//time_last_update_verified: u64,
+	// App ID -> size on disk, for every app Steam thinks is installed here
+	#[serde(alias = "apps", default)]
+	apps: IndexMap<String, String>
+}
+
+//
+// SteamLibrary/appmanifest_4000.acf
+//
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SteamAppManifest {
+	//appid: u64,
+	//universe: u8, // 0-5
+	//launcher_path: String,
+	//name: String,
+	#[serde(alias = "stateflags")]
+	pub state_flags: u32, // https://github.com/SteamDatabase/SteamTracking/blob/master/Structs/EAppState.json
+	#[serde(alias = "installdir")]
+	pub install_dir: String,
+	#[serde(alias = "lastupdated", default)]
+	pub last_updated: u64, // Y2K38
+	//last_played: u64,
+	//size_on_disk: u64,
+	#[serde(alias = "buildid", default)]
+	pub build_id: u32,
+	#[serde(alias = "lastowner", default)]
+	pub last_owner: u64,
+	//download_type: u32, // TODO: Is this right? Can't find documentation anywhere
+	//update_result: u32, // TODO: Is this right? Can't find documentation anywhere
+	#[serde(alias = "bytestodownload", default)]
+	pub bytes_to_download: u64,
+	#[serde(alias = "bytesdownloaded", default)]
+	pub bytes_downloaded: u64,
+	#[serde(alias = "bytestostage", default)]
+	pub bytes_to_stage: u64,
+	#[serde(alias = "bytesstaged", default)]
+	pub bytes_staged: u64,
+	//target_build_id: u32,
+	//auto_update_behavior: u8, // 1-3
+	//allow_other_downloads_while_running: bool,
+	#[serde(alias = "scheduledautoupdate")]
+	pub scheduled_auto_update: u64, // Y2K38
+	#[serde(alias = "fullvalidatebeforenextupdate")]
+	pub full_validate_before_next_update: Option<bool>,
+	//full_validate_after_next_update: bool,
+	// Depot ID -> what's installed from it
+	#[serde(alias = "installeddepots", default)]
+	pub installed_depots: IndexMap<String, SteamInstalledDepot>,
+	//shared_depots: ,
+	//user_config: SteamAppConfig,
+	#[serde(alias = "mountedconfig")]
+	pub mounted_config: SteamAppConfig
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SteamInstalledDepot {
+	// Depot manifest ID (a u64, but kept as Steam writes it)
+	pub manifest: String,
+	#[serde(default)]
+	pub size: u64
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SteamAppConfig {
+	#[serde(alias = "betakey")]
+	pub beta_key: Option<String>,
+	//language: Option<String>
+}
+
+//
+// Steam/config/config.vdf
+//
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamConfig {
+	#[serde(alias = "software")]
+	software: SteamConfigSoftware
+	// Several entries unimplemented!
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamConfigSoftware {
+	#[serde(alias = "valve")]
+	valve: SteamConfigValve
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamConfigValve {
+	#[serde(alias = "steam")]
+	steam: SteamConfigSteam
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamConfigSteam {
+	// App ID -> Compat Tool
+	#[serde(alias = "compattoolmapping", default)]
+	compat_tool_mapping: IndexMap<String, SteamCompatToolMapping>
+	// Several entries unimplemented!
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamCompatToolMapping {
+	#[serde(alias = "name")]
+	name: String,
+	//config: ,
+	//priority:
+}
+
+//
+// compatibilitytools.d/<tool>/compatibilitytool.vdf
+//
+#[derive(Deserialize, Debug)]
+struct CompatibilityToolManifest {
+	// Internal Name -> Tool
+	compat_tools: IndexMap<String, CompatibilityToolManifestTool>
+}
+
+#[derive(Deserialize, Debug)]
+struct CompatibilityToolManifestTool {
+	install_path: String,
+	//display_name: String,
+	//from_oslist: String,
+	//to_oslist: String
+}
+
+//
+// ~/.steam/registry.vdf (Linux)
+//
+#[derive(Deserialize, Debug)]
+struct SteamRegistryFile {
+	#[serde(rename = "HKCU", alias = "hkcu")]
+	hkcu: SteamRegistrySoftware
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamRegistrySoftware {
+	#[serde(alias = "software")]
+	software: SteamRegistryValve
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamRegistryValve {
+	#[serde(alias = "valve")]
+	valve: SteamRegistrySteam
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamRegistrySteam {
+	#[serde(alias = "steam")]
+	steam: SteamRegistryValues
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct SteamRegistryValues {
+	#[serde(alias = "sourcemodinstallpath")]
+	source_mod_install_path: Option<String>,
+	#[serde(rename = "RunningAppID", alias = "runningappid", default)]
+	running_app_id: u32,
+	#[serde(alias = "steampath")]
+	steam_path: Option<String>
+	// Several entries unimplemented!
+}
+
+pub const GMOD_APP_ID: u32 = 4000;
+
+// A Steam install directory (NOT a Steam library)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SteamInstallation {
+	pub path: PathBuf
+}
+
+// What Steam records about itself on Linux, from ~/.steam/registry.vdf and ~/.steam/steam.pid
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SteamRegistry {
+	// Where Steam is installed
+	pub steam_path: Option<PathBuf>,
+	// steamapps/sourcemods in the Steam install
+	pub source_mod_install_path: Option<PathBuf>,
+	// App that's running right now, or 0; only meaningful while Steam is running
+	pub running_app_id: u32,
+	// From steam.pid; may be left over from a Steam that crashed
+	// None for Flatpak, where it's from inside the sandbox's own PID namespace and means something else (or nothing) out here
+	pub pid: Option<u32>
+}
+
+// Someone who has signed in to Steam, from loginusers.vdf
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SteamUser {
+	pub steam_id_64: String,
+	pub account_name: String,
+	pub persona_name: String,
+	pub most_recent: bool,
+	pub timestamp: u64
+}
+
+// A Steam library from libraryfolders.vdf
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SteamLibrary {
+	pub path: PathBuf,
+	// Apps Steam thinks are installed here
+	pub app_ids: Vec<String>
+}
+
+// An app's manifest, and the library it's in
+#[derive(Debug)]
+pub struct AppInstall {
+	pub library_path: PathBuf,
+	pub manifest: SteamAppManifest,
+	// Whether Steam's libraryfolders.vdf agrees it's installed here; leftover manifests on old drives aren't
+	pub listed_by_steam: bool
+}
+
+// A compat tool Steam runs an app through (Proton, Steam Linux Runtime, etc)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompatTool {
+	// Internal name from config.vdf, lowercased
+	pub name: String,
+	// Where it's installed, if we could find it
+	pub path: Option<PathBuf>,
+	// From its version file (e.g. proton-9.0-2 or GE-Proton9-20)
+	pub version: Option<String>
+}
+
+// Steam Linux Runtime compat tools, which run native apps in a pressure-vessel container
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinuxRuntime {
+	// 1.0: Steam Runtime 1 libraries, inside the soldier container
+	Scout,
+	// 2.0: Steam Runtime 2, based on Debian 10
+	Soldier,
+	// 3.0: Steam Runtime 3, based on Debian 11
+	Sniper,
+	// Something newer we don't know about yet
+	Unknown
+}
+
+// An app's Wine prefix, from steamapps/compatdata/<appid>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompatPrefix {
+	// compatdata/<appid>/pfx
+	pub path: PathBuf,
+	// Whether Proton has finished setting it up (it does this the first time the app is launched)
+	pub initialized: bool,
+	// Proton version that last set it up (e.g. 9.0-2 or GE-Proton9-20)
+	pub version: Option<String>
+}
+
+// An app's branch (beta), from Steam's app info cache
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppBranch {
+	pub name: String,
+	// Latest build on it that Steam knows about
+	pub build_id: u32,
+	pub description: Option<String>,
+	pub password_required: bool,
+	pub time_updated: Option<u64>
+}
+
+impl CompatTool {
+	pub fn is_proton(&self) -> bool {
+		self.name.contains("proton")
+	}
+
+	pub fn major_version(&self) -> Option<u32> {
+		self.version.as_deref().and_then(parse_major_version)
+	}
+
+	pub fn linux_runtime(&self) -> Option<LinuxRuntime> {
+		LinuxRuntime::from_compat_tool_name(&self.name)
+	}
+}
+
+impl LinuxRuntime {
+	pub fn from_compat_tool_name(name: &str) -> Option<LinuxRuntime> {
+		match name.to_lowercase().as_str() {
+			"steamlinuxruntime" => Some(LinuxRuntime::Scout),
+			"steamlinuxruntime_soldier" => Some(LinuxRuntime::Soldier),
+			"steamlinuxruntime_sniper" => Some(LinuxRuntime::Sniper),
+			name if name.starts_with("steamlinuxruntime") => Some(LinuxRuntime::Unknown),
+			_ => None
+		}
+	}
+
+	// The container native apps run in
+	pub fn container(&self) -> &'static str {
+		match self {
+			LinuxRuntime::Scout => "scout on soldier (Steam Runtime 1 libraries in Steam Runtime 2)",
+			LinuxRuntime::Soldier => "soldier (Steam Runtime 2)",
+			LinuxRuntime::Sniper => "sniper (Steam Runtime 3)",
+			LinuxRuntime::Unknown => "unknown"
+		}
+	}
+}
+
+impl CompatPrefix {
+	pub fn major_version(&self) -> Option<u32> {
+		self.version.as_deref().and_then(parse_major_version)
+	}
+}
+
+impl AppInstall {
+	// steamapps/common/<installdir>
+	pub fn find_install_dir(&self) -> Option<PathBuf> {
+		// TODO: What about `steamapps/<username>/GarrysMod`? Is that still a thing, or did SteamPipe kill/migrate it completely?
+		// steamapps, then SteamApps with capitalization
+		["steamapps", "SteamApps"].iter()
+			.find_map(|steamapps| pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(self.library_path.clone(), &[steamapps, "common", &self.manifest.install_dir]), true).ok())
+	}
+}
+
+impl SteamInstallation {
+	pub fn new(path: PathBuf) -> SteamInstallation {
+		SteamInstallation {
+			path
+		}
+	}
+
+	// Everyone who has signed in to this Steam install
+	pub fn read_users(&self) -> Result<Vec<SteamUser>, String> {
+		let steam_loginusers_path = extend_pathbuf_and_return(self.path.clone(), &["config", "loginusers.vdf"]);
+		let steam_loginusers_str = std::fs::read_to_string(steam_loginusers_path)
+			.map_err(|_| "Couldn't find Steam loginusers.vdf. Have you ever launched/signed in to Steam?".to_string())?;
+
+		let steam_loginusers: IndexMap<String, SteamLoginUser> = vdf::from_str(steam_loginusers_str.as_str())
+			.map_err(|error| format!("Couldn't parse Steam loginusers.vdf. Is the file corrupt?\n\t{error}"))?;
+
+		Ok(steam_loginusers.into_iter().map(|(steam_id_64, steam_user)| SteamUser {
+			steam_id_64,
+			account_name: steam_user.account_name,
+			persona_name: steam_user.persona_name,
+			most_recent: steam_user.most_recent,
+			timestamp: steam_user.timestamp
+		}).collect())
+	}
+
+	// Steam Libraries that still exist, in the order Steam lists them
+	pub fn read_libraries(&self) -> Result<Vec<SteamLibrary>, String> {
+		// config, then steamapps, then SteamApps with capitalization
+		let steam_libraryfolders_str = [["config", "libraryfolders.vdf"], ["steamapps", "libraryfolders.vdf"], ["SteamApps", "libraryfolders.vdf"]].iter()
+			.find_map(|path_parts| std::fs::read_to_string(extend_pathbuf_and_return(self.path.clone(), path_parts)).ok())
+			.ok_or("Couldn't find Steam libraryfolders.vdf. Have you ever launched/signed in to Steam?".to_string())?;
+
+		let steam_libraryfolders: IndexMap<String, SteamLibraryFolder> = vdf::from_str(steam_libraryfolders_str.as_str())
+			.map_err(|error| format!("Couldn't parse Steam libraryfolders.vdf. Is the file corrupt?\n\t{error}"))?;
+
+		Ok(steam_libraryfolders.into_values().filter_map(|steam_library| {
+			string_to_canonical_pathbuf(steam_library.path).map(|path| SteamLibrary {
+				path,
+				app_ids: steam_library.apps.into_keys().collect()
+			})
+		}).collect())
+	}
+
+	// Every library with the app in it, not just the one Steam uses
+	pub fn find_app_installs(&self, app_id: u32) -> Result<Vec<AppInstall>, String> {
+		let app_id_str = app_id.to_string();
+		let mut app_installs = vec![];
+
+		for steam_library in self.read_libraries()? {
+			if let Some(app_manifest_str) = steam_library.read_app_manifest(app_id) {
+				let app_manifest = vdf::from_str(app_manifest_str.as_str())
+					.map_err(|error| format!("Couldn't parse appmanifest_{app_id}.acf in {}. Is the file corrupt?\n\t{error}", steam_library.path.display()))?;
+
+				app_installs.push(AppInstall {
+					listed_by_steam: steam_library.app_ids.contains(&app_id_str),
+					library_path: steam_library.path,
+					manifest: app_manifest
+				});
+			}
+		}
+
+		Ok(app_installs)
+	}
+
+	pub fn find_gmod_installs(&self) -> Result<Vec<AppInstall>, String> {
+		self.find_app_installs(GMOD_APP_ID)
+	}
+
+	pub fn has_gmod(&self) -> bool {
+		self.read_libraries().is_ok_and(|steam_libraries| steam_libraries.iter().any(|steam_library| steam_library.read_app_manifest(GMOD_APP_ID).is_some()))
+	}
+
+	// An app's entry in Steam's app info cache (appcache/appinfo.vdf)
+	// None if Steam hasn't cached it (yet)
+	pub fn read_app_info(&self, app_id: u32) -> Result<Option<vdf::AppInfo>, String> {
+		let app_info_path = extend_pathbuf_and_return(self.path.clone(), &["appcache", "appinfo.vdf"]);
+
+		let Ok(app_info_bytes) = std::fs::read(app_info_path) else {
+			return Ok(None);
+		};
+
+		vdf::read_app_info(&app_info_bytes, app_id).map_err(|error| format!("Couldn't parse Steam appinfo.vdf. Is the file corrupt?\n\t{error}"))
+	}
+
+	// An app's branches and their latest builds, straight from Steam
+	// Empty if Steam hasn't cached the app (yet)
+	pub fn read_app_branches(&self, app_id: u32) -> Result<Vec<AppBranch>, String> {
+		let app_info = self.read_app_info(app_id)?;
+
+		let Some(branches) = app_info.as_ref().and_then(|app_info| app_info.data.get_path(&["appinfo", "depots", "branches"])).and_then(vdf::BinaryValue::as_section) else {
+			return Ok(vec![]);
+		};
+
+		Ok(branches.iter().filter_map(|(name, branch)| {
+			let branch = branch.as_section()?;
+
+			Some(AppBranch {
+				name: name.to_string(),
+				build_id: u32::try_from(branch.get("buildid")?.as_u64()?).ok()?,
+				description: branch.get("description").and_then(vdf::BinaryValue::as_str).map(str::to_string),
+				password_required: branch.get("pwdrequired").and_then(vdf::BinaryValue::as_u64) == Some(1),
+				time_updated: branch.get("timeupdated").and_then(vdf::BinaryValue::as_u64)
+			})
+		}).collect())
+	}
+
+	fn read_compat_tool_mappings(&self) -> Result<IndexMap<String, SteamCompatToolMapping>, String> {
+		let steam_config_path = extend_pathbuf_and_return(self.path.clone(), &["config", "config.vdf"]);
+		let steam_config_str = std::fs::read_to_string(steam_config_path)
+			.map_err(|_| "Couldn't find Steam config.vdf. Have you ever launched/signed in to Steam?".to_string())?;
+
+		let steam_config: SteamConfig = vdf::from_str(steam_config_str.as_str())
+			.map_err(|_| "Couldn't parse Steam config.vdf. Is the file corrupt?".to_string())?;
+
+		Ok(steam_config.software.valve.steam.compat_tool_mapping)
+	}
+
+	// The compat tool (Proton, Steam Linux Runtime, etc) the app is set to use, lowercased
+	// None if it isn't set to use one (an empty name means it was set to run without one)
+	// The global default (see read_global_compat_tool) isn't included, since Steam only uses it for apps without a native Linux build
+	pub fn read_compat_tool(&self, app_id: u32) -> Result<Option<String>, String> {
+		Ok(self.read_compat_tool_mappings()?.get(&app_id.to_string()).filter(|compat_tool_mapping| !compat_tool_mapping.name.is_empty()).map(|compat_tool_mapping| compat_tool_mapping.name.to_lowercase()))
+	}
+
+	// The compat tool from "Enable Steam Play for all other titles" (app ID 0), lowercased
+	pub fn read_global_compat_tool(&self) -> Result<Option<String>, String> {
+		self.read_compat_tool(0)
+	}
+
+	// Where a compat tool from read_compat_tool is installed
+	// Custom ones (compatibilitytools.d) win over built-in ones (steamapps/common), same as Steam
+	pub fn find_compat_tool(&self, name: &str) -> CompatTool {
+		self.find_compat_tool_in(name, Path::new("/"))
+	}
+
+	// Same as find_compat_tool, but with the system-wide compatibilitytools.d relative to any root directory
+	pub fn find_compat_tool_in(&self, name: &str, root_dir: &Path) -> CompatTool {
+		let name = name.to_lowercase();
+		let path = self.find_custom_compat_tool(&name, root_dir).or_else(|| self.find_builtin_compat_tool(&name));
+
+		// e.g. "1718888888 proton-9.0-2"; we only want the last part
+		// Steam Linux Runtime has a "depot" line in VERSIONS.txt instead
+		let version = path.as_ref().and_then(|path| {
+			std::fs::read_to_string(path.join("version")).ok()
+				.and_then(|version_str| version_str.split_whitespace().last().map(str::to_string))
+				.or_else(|| {
+					std::fs::read_to_string(path.join("VERSIONS.txt")).ok()?
+						.lines()
+						.find_map(|line| line.strip_prefix("depot\t"))
+						.and_then(|line| line.split_whitespace().next().map(str::to_string))
+				})
+		});
+
+		CompatTool {
+			name,
+			path,
+			version
+		}
+	}
+
+	fn find_custom_compat_tool(&self, name: &str, root_dir: &Path) -> Option<PathBuf> {
+		let compat_tools_paths = [
+			extend_pathbuf_and_return(self.path.clone(), &["compatibilitytools.d"]),
+			// System-wide, from distro packages
+			extend_pathbuf_and_return(root_dir.to_path_buf(), &["usr", "share", "steam", "compatibilitytools.d"]),
+			extend_pathbuf_and_return(root_dir.to_path_buf(), &["usr", "local", "share", "steam", "compatibilitytools.d"])
+		];
+
+		for compat_tools_path in compat_tools_paths {
+			let Ok(compat_tools_dir) = std::fs::read_dir(compat_tools_path) else {
+				continue;
+			};
+
+			for compat_tool_dir in compat_tools_dir.flatten() {
+				let Ok(compat_tool_manifest_str) = std::fs::read_to_string(compat_tool_dir.path().join("compatibilitytool.vdf")) else {
+					continue;
+				};
+
+				// Broken ones are skipped, same as Steam
+				let Ok(compat_tool_manifest) = vdf::from_str::<CompatibilityToolManifest>(compat_tool_manifest_str.as_str()) else {
+					continue;
+				};
+
+				for (compat_tool_name, compat_tool) in compat_tool_manifest.compat_tools {
+					if compat_tool_name.to_lowercase() == name && let Ok(compat_tool_path) = pathbuf_to_canonical_pathbuf(compat_tool_dir.path().join(compat_tool.install_path), true) {
+						return Some(compat_tool_path);
+					}
+				}
+			}
+		}
+
+		None
+	}
+
+	fn find_builtin_compat_tool(&self, name: &str) -> Option<PathBuf> {
+		for steam_library in self.read_libraries().unwrap_or_default() {
+			// steamapps, then SteamApps with capitalization
+			for steamapps in ["steamapps", "SteamApps"] {
+				let Ok(common_dir) = std::fs::read_dir(extend_pathbuf_and_return(steam_library.path.clone(), &[steamapps, "common"])) else {
+					continue;
+				};
+
+				for app_dir in common_dir.flatten() {
+					let app_dir_name = app_dir.file_name().to_string_lossy().to_lowercase();
+
+					// Steam Linux Runtime directories are just their internal names with different capitalization
+					if (app_dir_name == name || get_builtin_proton_name(&app_dir_name).as_deref() == Some(name)) && let Ok(app_path) = pathbuf_to_canonical_pathbuf(app_dir.path(), true) {
+						return Some(app_path);
+					}
+				}
+			}
+		}
+
+		None
+	}
+}
+
+impl SteamLibrary {
+	pub fn new(path: PathBuf) -> SteamLibrary {
+		SteamLibrary {
+			path,
+			app_ids: vec![]
+		}
+	}
+
+	// Contents of the app's appmanifest_<appid>.acf, if it has one here
+	pub fn read_app_manifest(&self, app_id: u32) -> Option<String> {
+		let app_manifest_filename = format!("appmanifest_{app_id}.acf");
+
+		// steamapps, then SteamApps with capitalization
+		["steamapps", "SteamApps"].iter()
+			.find_map(|steamapps| std::fs::read_to_string(extend_pathbuf_and_return(self.path.clone(), &[steamapps, &app_manifest_filename])).ok())
+	}
+
+	// The app's Wine prefix, if Proton has made one here
+	pub fn read_compat_prefix(&self, app_id: u32) -> Option<CompatPrefix> {
+		let app_id_str = app_id.to_string();
+
+		// steamapps, then SteamApps with capitalization
+		let compat_data_path = ["steamapps", "SteamApps"].iter()
+			.find_map(|steamapps| pathbuf_to_canonical_pathbuf(extend_pathbuf_and_return(self.path.clone(), &[steamapps, "compatdata", &app_id_str]), true).ok())?;
+
+		let path = extend_pathbuf_and_return(compat_data_path.clone(), &["pfx"]);
+
+		// Proton creates pfx before it's done with it, so look for what it makes at the end too
+		let initialized = path.join("system.reg").is_file() && extend_pathbuf_and_return(path.clone(), &["drive_c", "windows"]).is_dir();
+
+		let version = std::fs::read_to_string(compat_data_path.join("version")).ok()
+			.and_then(|version_str| version_str.split_whitespace().last().map(str::to_string));
+
+		Some(CompatPrefix {
+			path,
+			initialized,
+			version
+		})
+	}
+}
+
+impl SteamRegistry {
+	// Whether the Steam from steam.pid is still running
+	pub fn is_steam_running(&self) -> bool {
+		let Some(pid) = self.pid else {
+			return false;
+		};
+
+		// The PID may have been reused since Steam last ran
+		match std::fs::read_to_string(format!("/proc/{pid}/comm")) {
+			Ok(process_name) => process_name.trim() == "steam",
+			Err(_) => false
+		}
+	}
+
+	// Whether Steam says the app is running; if Steam itself isn't running, we can't know
+	pub fn is_app_running(&self, app_id: u32) -> Option<bool> {
+		self.is_steam_running().then_some(self.running_app_id == app_id)
+	}
+}
+
+// Internal name of a built-in Proton from its steamapps/common directory (lowercased)
+// e.g. "proton 9.0 (beta)" is proton_9, "proton 5.13" is proton_513, "proton - experimental" is proton_experimental
+fn get_builtin_proton_name(app_dir_name: &str) -> Option<String> {
+	let proton_version = app_dir_name.strip_prefix("proton")?.trim_start_matches([' ', '-']);
+
+	if proton_version.is_empty() {
+		return None;
+	}
+
+	let proton_version = proton_version.split_whitespace().next()?;
+	let (proton_major, proton_minor) = proton_version.split_once('.').unwrap_or((proton_version, "0"));
+
+	if !proton_major.chars().all(|c| c.is_ascii_digit()) {
+		// Experimental, Hotfix, etc
+		return Some(format!("proton_{proton_major}"));
+	}
+
+	if proton_minor == "0" {
+		Some(format!("proton_{proton_major}"))
+	} else {
+		Some(format!("proton_{proton_major}{proton_minor}"))
+	}
+}
+
+// First number in a Proton version (e.g. 9 for proton-9.0-2, GE-Proton9-20, or 9.0-2)
+fn parse_major_version(version: &str) -> Option<u32> {
+	let major_start = version.find(|c: char| c.is_ascii_digit())?;
+	let major_str: String = version[major_start..].chars().take_while(char::is_ascii_digit).collect();
+
+	major_str.parse().ok()
+}
+
+// --steam-user (SteamID64 or account name), otherwise whoever owns the app (LastOwner), otherwise the most recent user, which is probably the one they're using/want
+// Returns the index into users
+pub fn select_user(users: &[SteamUser], user_arg: Option<&str>, app_last_owner: u64) -> Result<usize, String> {
+	if users.is_empty() {
+		return Err("Couldn't find Steam User. Have you ever launched/signed in to Steam?".to_string());
+	}
+
+	if let Some(user_arg) = user_arg {
+		let user_arg = user_arg.trim();
+
+		return users.iter()
+			.position(|user| user.steam_id_64 == user_arg || user.account_name.eq_ignore_ascii_case(user_arg))
+			.ok_or_else(|| {
+				let users_str: String = users.iter().map(|user| format!("\n\t- {} ({} / {})", user.persona_name, user.steam_id_64, user.account_name)).collect();
+				format!("Couldn't find Steam User {user_arg}. These users have signed in to this Steam install:{users_str}")
+			});
+	}
+
+	if app_last_owner != 0 {
+		let app_last_owner = app_last_owner.to_string();
+
+		if let Some(user_id) = users.iter().position(|user| user.steam_id_64 == app_last_owner) {
+			return Ok(user_id);
+		}
+	}
+
+	let mut user_id = 0;
+	for (other_user_id, other_user) in users.iter().enumerate() {
+		if other_user.most_recent || other_user.timestamp > users[user_id].timestamp {
+			user_id = other_user_id;
+		}
+	}
+
+	Ok(user_id)
+}
+
+// Every Steam install we can find on this system, most likely first
+pub fn find_installations() -> Vec<SteamInstallation> {
+	#[cfg(windows)]
+	{
+		let mut steam_paths: Vec<PathBuf> = vec![];
+
+		if let Ok(steam_reg_key) = windows_registry::CURRENT_USER.open("Software\\Valve\\Steam") {
+			if let Ok(steam_reg_path) = steam_reg_key.get_string("SteamPath") {
+				steam_paths.extend(string_to_canonical_pathbuf(steam_reg_path));
+			}
+		}
+
+		steam_paths.into_iter().map(SteamInstallation::new).collect()
+	}
+
+	#[cfg(not(windows))]
+	{
+		match dirs::home_dir() {
+			Some(home_dir) => find_installations_in(&home_dir, dirs::data_dir().as_deref()),
+			None => vec![]
+		}
+	}
+}
+
+// Steam's registry.vdf and steam.pid on Linux, if it has ever run
+pub fn read_registry() -> Option<SteamRegistry> {
+	read_registry_in(&dirs::home_dir()?)
+}
+
+// Same as read_registry, but relative to any home directory
+pub fn read_registry_in(home_dir: &Path) -> Option<SteamRegistry> {
+	// Home, then Snap, then Flatpak (whose steam.pid we can't use)
+	let steam_dot_paths = [
+		(extend_pathbuf_and_return(home_dir.to_path_buf(), &[".steam"]), true),
+		(extend_pathbuf_and_return(home_dir.to_path_buf(), &["snap", "steam", "common", ".steam"]), true),
+		(extend_pathbuf_and_return(home_dir.to_path_buf(), &[".var", "app", "com.valvesoftware.Steam", ".steam"]), false)
+	];
+
+	for (steam_dot_path, host_pid) in steam_dot_paths {
+		let Ok(steam_registry_str) = std::fs::read_to_string(steam_dot_path.join("registry.vdf")) else {
+			continue;
+		};
+
+		let Ok(steam_registry) = vdf::from_str::<SteamRegistryFile>(steam_registry_str.as_str()) else {
+			continue;
+		};
+
+		let steam_registry = steam_registry.hkcu.software.valve.steam;
+
+		let pid = std::fs::read_to_string(steam_dot_path.join("steam.pid")).ok()
+			.and_then(|pid_str| pid_str.trim().parse().ok())
+			.filter(|_| host_pid);
+
+		return Some(SteamRegistry {
+			steam_path: steam_registry.steam_path.and_then(string_to_canonical_pathbuf),
+			source_mod_install_path: steam_registry.source_mod_install_path.and_then(string_to_canonical_pathbuf),
+			running_app_id: steam_registry.running_app_id,
+			pid
+		});
+	}
+
+	None
+}
+
+// Same as find_installations, but relative to any home directory (and $XDG_DATA_HOME)
+// Doesn't apply to Windows, which only has the registry
+pub fn find_installations_in(home_dir: &Path, data_dir: Option<&Path>) -> Vec<SteamInstallation> {
+	let mut possible_steam_paths = if cfg!(target_os = "macos") {
+		// $HOME/Library/Application Support/Steam
+		vec![extend_pathbuf_and_return(home_dir.to_path_buf(), &["Library", "Application Support", "Steam"])]
+	} else {
+		// Anything else (we assume Linux)
+		// Wherever Steam says it is comes first, then the usual places
+		let steam_registry = read_registry_in(home_dir);
+		let steam_registry_path = steam_registry.and_then(|steam_registry| {
+			// SourceModInstallPath is <Steam>/steamapps/sourcemods
+			steam_registry.steam_path.or_else(|| steam_registry.source_mod_install_path.and_then(|source_mod_install_path| source_mod_install_path.ancestors().nth(2).map(Path::to_path_buf)))
+		});
+
+		steam_registry_path.into_iter().chain([
+			// Snap
+			extend_pathbuf_and_return(home_dir.to_path_buf(), &["snap", "steam", "common", ".local", "share", "Steam"]),
+			extend_pathbuf_and_return(home_dir.to_path_buf(), &["snap", "steam", "common", ".steam", "steam"]),
+			// Flatpak
+			extend_pathbuf_and_return(home_dir.to_path_buf(), &[".var", "app", "com.valvesoftware.Steam", ".local", "share", "Steam"]),
+			extend_pathbuf_and_return(home_dir.to_path_buf(), &[".var", "app", "com.valvesoftware.Steam", ".steam", "steam"]),
+			// Home
+			extend_pathbuf_and_return(home_dir.to_path_buf(), &[".steam", "steam"]),
+			//extend_pathbuf_and_return(home_dir.to_path_buf(), &[".steam"]),
+		]).collect()
+	};
+
+	// $XDG_DATA_HOME/Steam (on macOS, this is the same as above)
+	if let Some(data_dir) = data_dir {
+		possible_steam_paths.push(extend_pathbuf_and_return(data_dir.to_path_buf(), &["Steam"]));
+	}
+
+	// Several of these are usually symlinks to the same place
+	let mut steam_paths: Vec<PathBuf> = vec![];
+	for pathbuf in possible_steam_paths {
+		if let Ok(pathbuf) = pathbuf_to_canonical_pathbuf(pathbuf, true) && !steam_paths.contains(&pathbuf) {
+			steam_paths.push(pathbuf);
+		}
+	}
+
+	steam_paths.into_iter().map(SteamInstallation::new).collect()
+}
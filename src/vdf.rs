@@ -1,29 +1,44 @@
 use std::borrow::Cow;
+use std::fmt;
 
-use keyvalues_serde::parser::Vdf;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
 use serde::Deserialize;
 use thiserror::Error;
 
+// Steam's text KeyValues files (.vdf, .acf), and binary ones (appinfo.vdf, shortcuts.vdf)
+// Parsing never recurses, so huge or deeply nested files (e.g. localconfig.vdf's WebStorage) can't overflow the stack
+
 #[derive(Debug, Error)]
 pub enum Error {
-	#[error(transparent)]
-	KeyvaluesParser(Box<keyvalues_serde::parser::error::Error>),
-	#[error(transparent)]
-	KeyvaluesSerde(Box<keyvalues_serde::Error>),
+	#[error("{message} at byte {offset}")]
+	Syntax { message: &'static str, offset: usize },
+	#[error("{0}")]
+	Custom(String),
 	#[allow(clippy::enum_variant_names)]
 	#[error(transparent)]
-	SerdePathToError(Box<serde_path_to_error::Error<keyvalues_serde::Error>>),
+	SerdePathToError(Box<serde_path_to_error::Error<Error>>),
+	#[error("\"{0}\" is a value, not a section")]
+	NotASection(String),
+	#[error("\"{0}\" is a section, not a value")]
+	NotAValue(String),
+	#[error("Unsupported appinfo.vdf version ({0:#010x})")]
+	UnsupportedVersion(u32),
+}
+
+impl de::Error for Error {
+	fn custom<T: fmt::Display>(message: T) -> Self {
+		Error::Custom(message.to_string())
+	}
 }
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
 
 pub fn from_str_with_key<'de, T: Deserialize<'de>>(s: &'de str) -> Result<(T, Cow<'de, str>)> {
-	let vdf = Vdf::parse(s)
-		.map_err(Box::new)
-		.map_err(Error::KeyvaluesParser)?;
-	let (mut deserializer, key) = keyvalues_serde::Deserializer::new_with_key(vdf)
-		.map_err(Box::new)
-		.map_err(Error::KeyvaluesSerde)?;
+	let mut deserializer = Deserializer {
+		parser: Parser { s, pos: 0 }
+	};
+
+	let key = deserializer.parse_str()?;
 
 	let mut track = serde_path_to_error::Track::new();
 	let value = match T::deserialize(serde_path_to_error::Deserializer::new(
@@ -38,10 +53,9 @@ pub fn from_str_with_key<'de, T: Deserialize<'de>>(s: &'de str) -> Result<(T, Co
 		}
 	};
 
-	if !deserializer.is_empty() {
-		return Err(Error::KeyvaluesSerde(Box::new(
-			keyvalues_serde::Error::TrailingTokens,
-		)));
+	deserializer.parser.parse_trivia();
+	if deserializer.parser.peek().is_some() {
+		return Err(Error::Syntax { message: "Trailing tokens", offset: deserializer.parser.pos });
 	}
 
 	Ok((value, key))
@@ -50,3 +64,819 @@ pub fn from_str_with_key<'de, T: Deserialize<'de>>(s: &'de str) -> Result<(T, Co
 pub fn from_str<'de, T: Deserialize<'de>>(s: &'de str) -> Result<T> {
 	from_str_with_key(s).map(|(value, _key)| value)
 }
+
+//
+// Deserializing
+// Reads straight from the text, so sections the type doesn't have a field for are skipped over without being parsed into anything
+//
+struct Deserializer<'de> {
+	parser: Parser<'de>
+}
+
+impl<'de> Deserializer<'de> {
+	// The next key or string value
+	fn parse_str(&mut self) -> Result<Cow<'de, str>> {
+		self.parser.parse_trivia();
+
+		match self.parser.peek() {
+			Some(b'{') => Err(Error::Syntax { message: "Expected a value, found a section", offset: self.parser.pos }),
+			Some(b'}') | None => Err(Error::Syntax { message: "Expected a value", offset: self.parser.pos }),
+			Some(_) => Ok(get_token_value(self.parser.parse_token()?))
+		}
+	}
+
+	// Skips a value, or a whole section by counting braces
+	fn skip_value(&mut self) -> Result<()> {
+		self.parser.parse_trivia();
+
+		if self.parser.peek() != Some(b'{') {
+			return self.parse_str().map(|_| ());
+		}
+
+		self.parser.pos += 1;
+
+		let mut depth: usize = 1;
+		while depth > 0 {
+			self.parser.parse_trivia();
+
+			match self.parser.peek() {
+				Some(b'{') => {
+					self.parser.pos += 1;
+					depth += 1;
+				},
+				Some(b'}') => {
+					self.parser.pos += 1;
+					depth -= 1;
+				},
+				Some(_) => {
+					self.parser.parse_token()?;
+				},
+				None => return Err(Error::Syntax { message: "Missing }", offset: self.parser.pos })
+			}
+		}
+
+		Ok(())
+	}
+}
+
+macro_rules! deserialize_number {
+	($deserialize:ident, $visit:ident, $number:ty) => {
+		fn $deserialize<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+			let value = self.parse_str()?;
+			let number = value.trim().parse::<$number>().map_err(|error| Error::Custom(format!("{error}: \"{value}\"")))?;
+
+			visitor.$visit(number)
+		}
+	};
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+		self.parser.parse_trivia();
+
+		if self.parser.peek() == Some(b'{') {
+			self.parser.pos += 1;
+			return visitor.visit_map(SectionAccess { deserializer: self });
+		}
+
+		match self.parse_str()? {
+			Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+			Cow::Owned(value) => visitor.visit_string(value)
+		}
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+		match self.parse_str()?.as_ref() {
+			"0" => visitor.visit_bool(false),
+			"1" => visitor.visit_bool(true),
+			value => Err(Error::Custom(format!("Expected 0 or 1: \"{value}\"")))
+		}
+	}
+
+	deserialize_number!(deserialize_i8, visit_i8, i8);
+	deserialize_number!(deserialize_i16, visit_i16, i16);
+	deserialize_number!(deserialize_i32, visit_i32, i32);
+	deserialize_number!(deserialize_i64, visit_i64, i64);
+	deserialize_number!(deserialize_u8, visit_u8, u8);
+	deserialize_number!(deserialize_u16, visit_u16, u16);
+	deserialize_number!(deserialize_u32, visit_u32, u32);
+	deserialize_number!(deserialize_u64, visit_u64, u64);
+	deserialize_number!(deserialize_f32, visit_f32, f32);
+	deserialize_number!(deserialize_f64, visit_f64, f64);
+
+	// If the key is there, so is the value
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value> {
+		visitor.visit_enum(self.parse_str()?.into_owned().into_deserializer())
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+		self.skip_value()?;
+		visitor.visit_unit()
+	}
+
+	serde::forward_to_deserialize_any! {
+		i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+	}
+}
+
+// The keys and values of a section, up to its }
+struct SectionAccess<'a, 'de> {
+	deserializer: &'a mut Deserializer<'de>
+}
+
+impl<'de> MapAccess<'de> for SectionAccess<'_, 'de> {
+	type Error = Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+		let parser = &mut self.deserializer.parser;
+		parser.parse_trivia();
+
+		match parser.peek() {
+			Some(b'}') => {
+				parser.pos += 1;
+				Ok(None)
+			},
+			Some(b'{') => Err(Error::Syntax { message: "Unexpected {", offset: parser.pos }),
+			None => Err(Error::Syntax { message: "Missing }", offset: parser.pos }),
+			Some(_) => seed.deserialize(&mut *self.deserializer).map(Some)
+		}
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+		seed.deserialize(&mut *self.deserializer)
+	}
+}
+
+//
+// Document model, for editing Steam's files
+// Everything between tokens (whitespace, comments, conditionals) is kept, so an unedited document is written back byte for byte
+//
+
+// A key or string value as written (with any quotes and escapes), and what it means
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Token {
+	raw: String,
+	value: String
+}
+
+impl Token {
+	fn parsed(raw: &str) -> Token {
+		Token {
+			raw: raw.to_string(),
+			value: get_token_value(raw).into_owned()
+		}
+	}
+
+	fn quoted(value: &str) -> Token {
+		Token {
+			raw: format!("\"{}\"", escape(value)),
+			value: value.to_string()
+		}
+	}
+}
+
+enum Value {
+	Str(Token),
+	Obj(Object)
+}
+
+struct Item {
+	// Whatever is before the key, since the end of the last item (or the {)
+	leading: String,
+	key: Token,
+	// Whatever is between the key and the value
+	between: String,
+	value: Value
+}
+
+#[derive(Default)]
+struct Object {
+	items: Vec<Item>,
+	// Whatever is after the last item, before the } (or the end of the file)
+	trailing: String
+}
+
+impl Object {
+	// Keys aren't case sensitive to Steam, and it isn't consistent about them (e.g. "apps" in "Steam")
+	fn get(&self, key: &str) -> Option<&Item> {
+		self.items.iter().find(|item| item.key.value.eq_ignore_ascii_case(key))
+	}
+
+	fn get_mut(&mut self, key: &str) -> Option<&mut Item> {
+		self.items.iter_mut().find(|item| item.key.value.eq_ignore_ascii_case(key))
+	}
+}
+
+// Children are dropped one at a time, instead of recursively
+impl Drop for Object {
+	fn drop(&mut self) {
+		let mut items = std::mem::take(&mut self.items);
+
+		while let Some(item) = items.pop() {
+			if let Value::Obj(mut object) = item.value {
+				items.append(&mut object.items);
+			}
+		}
+	}
+}
+
+#[derive(Default)]
+pub struct Document {
+	root: Object
+}
+
+impl Document {
+	pub fn parse(s: &str) -> Result<Document> {
+		let mut parser = Parser { s, pos: 0 };
+
+		// Sections that are still open, with what goes before their { (leading, key, between)
+		let mut open_sections: Vec<(Object, String, Token, String)> = vec![];
+		let mut object = Object::default();
+
+		loop {
+			let leading = parser.parse_trivia().to_string();
+
+			match parser.peek() {
+				None => {
+					if !open_sections.is_empty() {
+						return Err(Error::Syntax { message: "Missing }", offset: parser.pos });
+					}
+
+					object.trailing = leading;
+
+					return Ok(Document { root: object });
+				},
+				Some(b'}') => {
+					let Some((parent, section_leading, section_key, section_between)) = open_sections.pop() else {
+						return Err(Error::Syntax { message: "Unexpected }", offset: parser.pos });
+					};
+
+					parser.pos += 1;
+					object.trailing = leading;
+
+					let section = std::mem::replace(&mut object, parent);
+					object.items.push(Item {
+						leading: section_leading,
+						key: section_key,
+						between: section_between,
+						value: Value::Obj(section)
+					});
+				},
+				Some(b'{') => return Err(Error::Syntax { message: "Unexpected {", offset: parser.pos }),
+				Some(_) => {
+					let key = Token::parsed(parser.parse_token()?);
+					let between = parser.parse_trivia().to_string();
+
+					match parser.peek() {
+						Some(b'{') => {
+							parser.pos += 1;
+							open_sections.push((std::mem::take(&mut object), leading, key, between));
+						},
+						Some(b'}') | None => return Err(Error::Syntax { message: "Key doesn't have a value", offset: parser.pos }),
+						Some(_) => {
+							let value = Token::parsed(parser.parse_token()?);
+							object.items.push(Item { leading, key, between, value: Value::Str(value) });
+						}
+					}
+				}
+			}
+		}
+	}
+
+	fn get_object(&self, path: &[&str]) -> Option<&Object> {
+		let mut object = &self.root;
+
+		for key in path {
+			match &object.get(key)?.value {
+				Value::Obj(child) => object = child,
+				Value::Str(_) => return None
+			}
+		}
+
+		Some(object)
+	}
+
+	// A string value by its path of keys, e.g. ["UserLocalConfigStore", "Software", "Valve", "Steam", "apps", "4000", "LaunchOptions"]
+	pub fn get(&self, path: &[&str]) -> Option<&str> {
+		let (key, parent_path) = path.split_last()?;
+
+		match &self.get_object(parent_path)?.get(key)?.value {
+			Value::Str(token) => Some(token.value.as_str()),
+			Value::Obj(_) => None
+		}
+	}
+
+	pub fn has_section(&self, path: &[&str]) -> bool {
+		!path.is_empty() && self.get_object(path).is_some()
+	}
+
+	// Keys in a section, in order (the top level for an empty path)
+	pub fn keys(&self, path: &[&str]) -> Option<Vec<&str>> {
+		Some(self.get_object(path)?.items.iter().map(|item| item.key.value.as_str()).collect())
+	}
+
+	// Sets a string value, adding it (and any sections on the way) if it isn't there, laid out like Steam does
+	pub fn set(&mut self, path: &[&str], value: &str) -> Result<()> {
+		let Some((key, parent_path)) = path.split_last() else {
+			return Err(Error::NotAValue(String::new()));
+		};
+
+		let mut object = &mut self.root;
+
+		for (depth, section_key) in parent_path.iter().enumerate() {
+			if object.get(section_key).is_none() {
+				object.items.push(Item {
+					leading: get_new_item_leading(object, depth),
+					key: Token::quoted(section_key),
+					between: format!("\n{}", "\t".repeat(depth)),
+					value: Value::Obj(Object {
+						items: vec![],
+						trailing: format!("\n{}", "\t".repeat(depth))
+					})
+				});
+			}
+
+			match &mut object.get_mut(section_key).unwrap().value {
+				Value::Obj(child) => object = child,
+				Value::Str(_) => return Err(Error::NotASection(path[..=depth].join("/")))
+			}
+		}
+
+		match object.get_mut(key) {
+			Some(Item { value: Value::Str(token), .. }) => *token = Token::quoted(value),
+			Some(Item { value: Value::Obj(_), .. }) => return Err(Error::NotAValue(path.join("/"))),
+			None => {
+				object.items.push(Item {
+					leading: get_new_item_leading(object, parent_path.len()),
+					key: Token::quoted(key),
+					between: "\t\t".to_string(),
+					value: Value::Str(Token::quoted(value))
+				});
+			}
+		}
+
+		Ok(())
+	}
+
+	// Removes a value or section, and the line it was on
+	// Returns whether it was there
+	pub fn remove(&mut self, path: &[&str]) -> bool {
+		let Some((key, parent_path)) = path.split_last() else {
+			return false;
+		};
+
+		let mut object = &mut self.root;
+
+		for section_key in parent_path {
+			match object.get_mut(section_key) {
+				Some(Item { value: Value::Obj(child), .. }) => object = child,
+				_ => return false
+			}
+		}
+
+		match object.items.iter().position(|item| item.key.value.eq_ignore_ascii_case(key)) {
+			Some(item_pos) => {
+				object.items.remove(item_pos);
+				true
+			},
+			None => false
+		}
+	}
+}
+
+impl fmt::Display for Document {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		// Sections being written, and the next item in each
+		let mut sections: Vec<(&Object, usize)> = vec![(&self.root, 0)];
+
+		while let Some((object, item_id)) = sections.pop() {
+			let Some(item) = object.items.get(item_id) else {
+				write!(f, "{}", object.trailing)?;
+
+				// The top level doesn't have braces
+				if !sections.is_empty() {
+					write!(f, "}}")?;
+				}
+
+				continue;
+			};
+
+			write!(f, "{}{}{}", item.leading, item.key.raw, item.between)?;
+			sections.push((object, item_id + 1));
+
+			match &item.value {
+				Value::Str(token) => write!(f, "{}", token.raw)?,
+				Value::Obj(child) => {
+					write!(f, "{{")?;
+					sections.push((child, 0));
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+// Each item on its own line, indented a tab per level
+fn get_new_item_leading(object: &Object, depth: usize) -> String {
+	if depth == 0 && object.items.is_empty() {
+		String::new()
+	} else {
+		format!("\n{}", "\t".repeat(depth))
+	}
+}
+
+fn escape(value: &str) -> String {
+	value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn unescape(value: &str) -> String {
+	let mut unescaped = String::with_capacity(value.len());
+	let mut chars = value.chars();
+
+	while let Some(char) = chars.next() {
+		if char != '\\' {
+			unescaped.push(char);
+			continue;
+		}
+
+		match chars.next() {
+			Some('n') => unescaped.push('\n'),
+			Some('t') => unescaped.push('\t'),
+			Some(escaped_char) => unescaped.push(escaped_char),
+			None => unescaped.push(char)
+		}
+	}
+
+	unescaped
+}
+
+struct Parser<'a> {
+	s: &'a str,
+	pos: usize
+}
+
+impl<'a> Parser<'a> {
+	fn peek(&self) -> Option<u8> {
+		self.s.as_bytes().get(self.pos).copied()
+	}
+
+	fn skip_until(&mut self, end: u8) {
+		while self.peek().is_some_and(|byte| byte != end) {
+			self.pos += 1;
+		}
+	}
+
+	// Whitespace, // comments, and conditionals like [$WIN32]
+	fn parse_trivia(&mut self) -> &'a str {
+		let start = self.pos;
+
+		loop {
+			match self.peek() {
+				Some(byte) if byte.is_ascii_whitespace() => self.pos += 1,
+				Some(b'/') if self.s.as_bytes().get(self.pos + 1) == Some(&b'/') => self.skip_until(b'\n'),
+				Some(b'[') => {
+					self.skip_until(b']');
+
+					if self.peek().is_some() {
+						self.pos += 1;
+					}
+				},
+				_ => break
+			}
+		}
+
+		&self.s[start..self.pos]
+	}
+
+	// A key or string value as written
+	fn parse_token(&mut self) -> Result<&'a str> {
+		let start = self.pos;
+
+		if self.peek() == Some(b'"') {
+			self.pos += 1;
+
+			while let Some(byte) = self.peek() {
+				match byte {
+					b'"' => break,
+					b'\\' => self.pos += 2,
+					_ => self.pos += 1
+				}
+			}
+
+			if self.pos >= self.s.len() {
+				return Err(Error::Syntax { message: "Unterminated string", offset: start });
+			}
+
+			self.pos += 1;
+		} else {
+			while self.peek().is_some_and(|byte| !byte.is_ascii_whitespace() && !matches!(byte, b'{' | b'}' | b'"')) {
+				self.pos += 1;
+			}
+		}
+
+		Ok(&self.s[start..self.pos])
+	}
+}
+
+// What a token means: without its quotes, and unescaped (only copied if it has escapes)
+fn get_token_value(raw: &str) -> Cow<'_, str> {
+	let Some(quoted) = raw.strip_prefix('"').and_then(|raw| raw.strip_suffix('"')) else {
+		return Cow::Borrowed(raw);
+	};
+
+	if quoted.contains('\\') {
+		Cow::Owned(unescape(quoted))
+	} else {
+		Cow::Borrowed(quoted)
+	}
+}
+
+//
+// Binary KeyValues, for appcache/appinfo.vdf and shortcuts.vdf
+//
+const BINARY_SECTION: u8 = 0x00;
+const BINARY_STRING: u8 = 0x01;
+const BINARY_INT32: u8 = 0x02;
+const BINARY_FLOAT32: u8 = 0x03;
+const BINARY_POINTER: u8 = 0x04;
+const BINARY_COLOR: u8 = 0x06;
+const BINARY_UINT64: u8 = 0x07;
+const BINARY_SECTION_END: u8 = 0x08;
+const BINARY_INT64: u8 = 0x0A;
+const BINARY_SECTION_END_ALT: u8 = 0x0B;
+
+// appinfo.vdf versions: 28 added a SHA-1 of each app's binary data, and 29 moved the keys into a string table at the end
+const APPINFO_MAGIC_27: u32 = 0x07564427;
+const APPINFO_MAGIC_28: u32 = 0x07564428;
+const APPINFO_MAGIC_29: u32 = 0x07564429;
+
+#[derive(Debug)]
+pub enum BinaryValue {
+	Section(BinarySection),
+	Str(String),
+	// Also pointers and colors
+	Int32(i32),
+	Float32(f32),
+	UInt64(u64),
+	Int64(i64)
+}
+
+impl BinaryValue {
+	pub fn as_section(&self) -> Option<&BinarySection> {
+		match self {
+			BinaryValue::Section(section) => Some(section),
+			_ => None
+		}
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			BinaryValue::Str(value) => Some(value),
+			_ => None
+		}
+	}
+
+	// Steam stores a lot of numbers (e.g. buildid) as strings
+	pub fn as_u64(&self) -> Option<u64> {
+		match self {
+			BinaryValue::Str(value) => value.trim().parse().ok(),
+			BinaryValue::Int32(value) => u64::try_from(*value).ok(),
+			BinaryValue::UInt64(value) => Some(*value),
+			BinaryValue::Int64(value) => u64::try_from(*value).ok(),
+			_ => None
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+pub struct BinarySection {
+	items: Vec<(String, BinaryValue)>
+}
+
+impl BinarySection {
+	// Keys aren't case sensitive, same as text KeyValues
+	pub fn get(&self, key: &str) -> Option<&BinaryValue> {
+		self.items.iter().find(|(item_key, _)| item_key.eq_ignore_ascii_case(key)).map(|(_, value)| value)
+	}
+
+	// A value by its path of keys, e.g. ["appinfo", "depots", "branches"]
+	pub fn get_path(&self, path: &[&str]) -> Option<&BinaryValue> {
+		let (key, parent_path) = path.split_last()?;
+		let mut section = self;
+
+		for section_key in parent_path {
+			section = section.get(section_key)?.as_section()?;
+		}
+
+		section.get(key)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &BinaryValue)> {
+		self.items.iter().map(|(key, value)| (key.as_str(), value))
+	}
+}
+
+// Children are dropped one at a time, instead of recursively
+impl Drop for BinarySection {
+	fn drop(&mut self) {
+		let mut items = std::mem::take(&mut self.items);
+
+		while let Some((_, value)) = items.pop() {
+			if let BinaryValue::Section(mut section) = value {
+				items.append(&mut section.items);
+			}
+		}
+	}
+}
+
+// One app's entry in appinfo.vdf
+#[derive(Debug)]
+pub struct AppInfo {
+	pub app_id: u32,
+	pub last_updated: u32,
+	pub change_number: u32,
+	// Usually just an "appinfo" section
+	pub data: BinarySection
+}
+
+struct BinaryReader<'a> {
+	// Cut off where the current part ends (e.g. an app's entry), so nothing can be read past it
+	bytes: &'a [u8],
+	pos: usize
+}
+
+impl<'a> BinaryReader<'a> {
+	fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+		let bytes = self.bytes.get(self.pos..self.pos.saturating_add(len))
+			.ok_or(Error::Syntax { message: "Unexpected end of file", offset: self.pos })?;
+
+		self.pos += len;
+
+		Ok(bytes)
+	}
+
+	fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+		Ok(self.read_bytes(N)?.try_into().unwrap())
+	}
+
+	fn read_u8(&mut self) -> Result<u8> {
+		Ok(self.read_array::<1>()?[0])
+	}
+
+	fn read_u32(&mut self) -> Result<u32> {
+		Ok(u32::from_le_bytes(self.read_array()?))
+	}
+
+	fn read_u64(&mut self) -> Result<u64> {
+		Ok(u64::from_le_bytes(self.read_array()?))
+	}
+
+	// Null-terminated
+	fn read_string(&mut self) -> Result<String> {
+		let len = self.bytes.get(self.pos..).unwrap_or_default().iter().position(|byte| *byte == 0)
+			.ok_or(Error::Syntax { message: "Unterminated string", offset: self.pos })?;
+
+		let string = String::from_utf8_lossy(self.read_bytes(len)?).into_owned();
+		self.pos += 1;
+
+		Ok(string)
+	}
+
+	// Keys are either written out, or (appinfo.vdf v29) an index into its string table
+	fn read_key(&mut self, string_table: Option<&[String]>) -> Result<String> {
+		let Some(string_table) = string_table else {
+			return self.read_string();
+		};
+
+		let offset = self.pos;
+		let key_id = self.read_u32()? as usize;
+
+		string_table.get(key_id).cloned().ok_or(Error::Syntax { message: "Key isn't in the string table", offset })
+	}
+
+	// Items up to the end of the section they're in (or the end of the data)
+	fn read_section(&mut self, string_table: Option<&[String]>) -> Result<BinarySection> {
+		// Sections that are still open, with their keys
+		let mut open_sections: Vec<(BinarySection, String)> = vec![];
+		let mut section = BinarySection::default();
+
+		loop {
+			let value_type = match self.read_u8() {
+				Ok(value_type) => value_type,
+				Err(_) if open_sections.is_empty() => return Ok(section),
+				Err(error) => return Err(error)
+			};
+
+			if value_type == BINARY_SECTION_END || value_type == BINARY_SECTION_END_ALT {
+				let Some((parent, key)) = open_sections.pop() else {
+					return Ok(section);
+				};
+
+				let child = std::mem::replace(&mut section, parent);
+				section.items.push((key, BinaryValue::Section(child)));
+
+				continue;
+			}
+
+			let offset = self.pos - 1;
+			let key = self.read_key(string_table)?;
+
+			let value = match value_type {
+				BINARY_SECTION => {
+					open_sections.push((std::mem::take(&mut section), key));
+					continue;
+				},
+				BINARY_STRING => BinaryValue::Str(self.read_string()?),
+				BINARY_INT32 | BINARY_POINTER | BINARY_COLOR => BinaryValue::Int32(i32::from_le_bytes(self.read_array()?)),
+				BINARY_FLOAT32 => BinaryValue::Float32(f32::from_le_bytes(self.read_array()?)),
+				BINARY_UINT64 => BinaryValue::UInt64(self.read_u64()?),
+				BINARY_INT64 => BinaryValue::Int64(i64::from_le_bytes(self.read_array()?)),
+				_ => return Err(Error::Syntax { message: "Unknown value type", offset })
+			};
+
+			section.items.push((key, value));
+		}
+	}
+}
+
+// A whole binary KeyValues file, like shortcuts.vdf
+pub fn from_binary_slice(bytes: &[u8]) -> Result<BinarySection> {
+	BinaryReader { bytes, pos: 0 }.read_section(None)
+}
+
+// Finds one app in appcache/appinfo.vdf
+// Every other app is skipped over without being parsed
+pub fn read_app_info(bytes: &[u8], app_id: u32) -> Result<Option<AppInfo>> {
+	let mut reader = BinaryReader { bytes, pos: 0 };
+
+	let magic = reader.read_u32()?;
+	let _universe = reader.read_u32()?;
+
+	let string_table = match magic {
+		APPINFO_MAGIC_27 | APPINFO_MAGIC_28 => None,
+		APPINFO_MAGIC_29 => {
+			let string_table_offset = reader.read_u64()? as usize;
+			let mut string_table_reader = BinaryReader { bytes, pos: string_table_offset };
+
+			let string_count = string_table_reader.read_u32()?;
+			let mut string_table = vec![];
+
+			for _ in 0..string_count {
+				string_table.push(string_table_reader.read_string()?);
+			}
+
+			Some(string_table)
+		},
+		_ => return Err(Error::UnsupportedVersion(magic))
+	};
+
+	loop {
+		let entry_app_id = reader.read_u32()?;
+
+		// The list of apps ends with app 0
+		if entry_app_id == 0 {
+			return Ok(None);
+		}
+
+		let entry_size = reader.read_u32()? as usize;
+		let entry_start = reader.pos;
+		reader.read_bytes(entry_size)?;
+
+		if entry_app_id != app_id {
+			continue;
+		}
+
+		let mut entry_reader = BinaryReader {
+			bytes: &bytes[..entry_start + entry_size],
+			pos: entry_start
+		};
+
+		let _info_state = entry_reader.read_u32()?;
+		let last_updated = entry_reader.read_u32()?;
+		let _pics_token = entry_reader.read_u64()?;
+		let _text_sha1 = entry_reader.read_array::<20>()?;
+		let change_number = entry_reader.read_u32()?;
+
+		if magic != APPINFO_MAGIC_27 {
+			let _binary_sha1 = entry_reader.read_array::<20>()?;
+		}
+
+		return Ok(Some(AppInfo {
+			app_id,
+			last_updated,
+			change_number,
+			data: entry_reader.read_section(string_table.as_deref())?
+		}));
+	}
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "patch")]
+
+use gmodpatchtool::launch_options::{self, LaunchOptions, LaunchOptionRule, Severity};
+
+fn builtin_rules() -> Vec<LaunchOptionRule> {
+	launch_options::parse_rules(launch_options::BUILTIN_RULES.as_bytes()).unwrap()
+}
+
+fn lint_ids(launch_options_str: &str, platform: &str, compat_tool: &str) -> Vec<String> {
+	LaunchOptions::parse(launch_options_str).lint(&builtin_rules(), platform, compat_tool).into_iter().map(|finding| finding.id).collect()
+}
+
+#[test]
+fn splits_around_command() {
+	let launch_options = LaunchOptions::parse("GMOD_ENABLE_LD_PRELOAD=1 gamemoderun %command% -chromium_fps_max 60 +map gm_construct");
+
+	assert_eq!(launch_options.prefix, vec!["GMOD_ENABLE_LD_PRELOAD=1", "gamemoderun"]);
+	assert_eq!(launch_options.args, vec![vec!["-chromium_fps_max", "60"], vec!["+map", "gm_construct"]]);
+	assert_eq!(launch_options.to_string(), "GMOD_ENABLE_LD_PRELOAD=1 gamemoderun %command% -chromium_fps_max 60 +map gm_construct");
+
+	// Without %command%, everything is an argument
+	let launch_options = LaunchOptions::parse("GMOD_ENABLE_LD_PRELOAD=1 -foo");
+
+	assert!(launch_options.prefix.is_empty());
+	assert_eq!(launch_options.args, vec![vec!["GMOD_ENABLE_LD_PRELOAD=1"], vec!["-foo"]]);
+	assert_eq!(launch_options.to_string(), "GMOD_ENABLE_LD_PRELOAD=1 -foo");
+
+	// %command% with nothing before it is kept
+	assert_eq!(LaunchOptions::parse("%command% -novid").to_string(), "%command% -novid");
+	assert!(LaunchOptions::parse("  ").is_empty());
+}
+
+#[test]
+fn keeps_quoted_spaces() {
+	let launch_options = LaunchOptions::parse("LD_PRELOAD=\"a.so b.so\" %command% +exec \"my config.cfg\"");
+
+	assert_eq!(launch_options.prefix, vec!["LD_PRELOAD=\"a.so b.so\""]);
+	assert_eq!(launch_options.args, vec![vec!["+exec", "\"my config.cfg\""]]);
+	assert_eq!(launch_options.items(), vec!["LD_PRELOAD=\"a.so b.so\"", "+exec \"my config.cfg\""]);
+}
+
+#[test]
+fn parses_input_without_command() {
+	let launch_options = LaunchOptions::parse_input("GMOD_ENABLE_LD_PRELOAD=1 -chromium_fps_max 60");
+
+	assert_eq!(launch_options.prefix, vec!["GMOD_ENABLE_LD_PRELOAD=1"]);
+	assert_eq!(launch_options.args, vec![vec!["-chromium_fps_max", "60"]]);
+}
+
+#[test]
+fn adds_and_replaces_options() {
+	let mut launch_options = LaunchOptions::parse("%command% -chromium_fps_max 30 -novid");
+	launch_options.add(&LaunchOptions::parse_input("-CHROMIUM_FPS_MAX 60 -windowed"));
+
+	assert_eq!(launch_options.to_string(), "%command% -CHROMIUM_FPS_MAX 60 -novid -windowed");
+
+	// Duplicates are collapsed into the one that was added
+	let mut launch_options = LaunchOptions::parse("A=1 A=2 %command%");
+	launch_options.add(&LaunchOptions::parse_input("A=3"));
+
+	assert_eq!(launch_options.to_string(), "A=3 %command%");
+}
+
+#[test]
+fn adds_env_var_without_command() {
+	// Adding an environment variable puts %command% after it, so the arguments stay arguments
+	let mut launch_options = LaunchOptions::parse("-novid");
+	launch_options.add(&LaunchOptions::parse_input("GMOD_ENABLE_LD_PRELOAD=1"));
+
+	assert_eq!(launch_options.to_string(), "GMOD_ENABLE_LD_PRELOAD=1 %command% -novid");
+	assert!(launch_options.enables_ld_preload());
+
+	// One that was in the arguments by mistake is moved
+	let mut launch_options = LaunchOptions::parse("GMOD_ENABLE_LD_PRELOAD=1 -novid");
+	launch_options.add(&LaunchOptions::parse_input("GMOD_ENABLE_LD_PRELOAD=1"));
+
+	assert_eq!(launch_options.to_string(), "GMOD_ENABLE_LD_PRELOAD=1 %command% -novid");
+}
+
+#[test]
+fn removes_options() {
+	let mut launch_options = LaunchOptions::parse("GMOD_ENABLE_LD_PRELOAD=1 %command% -NoChromium -chromium_fps_max 60");
+
+	assert!(launch_options.remove("-nochromium"));
+	assert!(launch_options.remove("GMOD_ENABLE_LD_PRELOAD"));
+	assert!(!launch_options.remove("-novid"));
+
+	assert_eq!(launch_options.to_string(), "%command% -chromium_fps_max 60");
+}
+
+#[test]
+fn only_counts_env_vars_before_command() {
+	assert!(LaunchOptions::parse("GMOD_ENABLE_LD_PRELOAD=1 %command%").enables_ld_preload());
+	assert!(!LaunchOptions::parse("GMOD_ENABLE_LD_PRELOAD=0 %command%").enables_ld_preload());
+	assert!(!LaunchOptions::parse("GMOD_ENABLE_LD_PRELOAD=1 -foo").enables_ld_preload());
+	assert!(!LaunchOptions::parse("%command% GMOD_ENABLE_LD_PRELOAD=1").enables_ld_preload());
+
+	// Passed to GMod as arguments, so LD_PRELOAD is still dropped
+	assert_eq!(lint_ids("GMOD_ENABLE_LD_PRELOAD=1 LD_PRELOAD=libfoo.so -foo", "linux", "none"), vec!["env_var_without_command", "env_var_without_command"]);
+	assert_eq!(lint_ids("GMOD_ENABLE_LD_PRELOAD=1 LD_PRELOAD=libfoo.so %command%", "linux", "none"), Vec::<String>::new());
+}
+
+#[test]
+fn lints_present_and_conflicting_options() {
+	let findings = LaunchOptions::parse("%command% -NOCHROMIUM").lint(&builtin_rules(), "linux", "none");
+
+	assert_eq!(findings.len(), 1);
+	assert_eq!(findings[0].id, "nochromium");
+	assert_eq!(findings[0].severity, Severity::Error);
+	assert!(findings[0].message.starts_with("-NOCHROMIUM is in"));
+
+	// Only a problem under Proton
+	assert_eq!(lint_ids("%command% -dxlevel 95 -gl", "windows", "proton_9"), vec!["dxlevel_gl_proton"]);
+	assert_eq!(lint_ids("%command% -dxlevel 95 -gl", "linux", "none"), Vec::<String>::new());
+	assert_eq!(lint_ids("%command% -dxlevel 95", "windows", "proton_9"), Vec::<String>::new());
+}
+
+#[test]
+fn lints_duplicates() {
+	let findings = LaunchOptions::parse("A=1 A=2 %command% -novid -NOVID -novid").lint(&builtin_rules(), "linux", "none");
+
+	assert_eq!(findings.iter().map(|finding| finding.message.split(' ').next().unwrap()).collect::<Vec<&str>>(), vec!["A=2", "-NOVID"]);
+}
+
+#[test]
+fn lints_required_options() {
+	assert_eq!(lint_ids("LD_PRELOAD=libfoo.so %command%", "linux", "none"), vec!["ld_preload_without_gmod_enable_ld_preload"]);
+	assert_eq!(lint_ids("GMOD_ENABLE_LD_PRELOAD=0 LD_PRELOAD=libfoo.so %command%", "linux", "none"), vec!["ld_preload_without_gmod_enable_ld_preload"]);
+
+	// Only native GMod on Linux uses hl2.sh
+	assert_eq!(lint_ids("LD_PRELOAD=libfoo.so %command%", "windows", "proton_9"), Vec::<String>::new());
+}
+
+#[test]
+fn skips_unknown_rules() {
+	let rules = launch_options::parse_rules(br#"{"rules": [{"id": "new", "severity": "warning", "check": "something_newer", "message": "", "fix": ""}, {"id": "nochromium", "severity": "info", "check": "present", "options": ["-nochromium"], "message": "{option}", "fix": ""}]}"#).unwrap();
+
+	assert_eq!(rules.len(), 1);
+
+	// Newer rules replace built-in ones with the same id
+	let rules = launch_options::merge_rules(builtin_rules(), rules);
+	let findings = LaunchOptions::parse("%command% -nochromium").lint(&rules, "linux", "none");
+
+	assert_eq!(findings.len(), 1);
+	assert_eq!(findings[0].severity, Severity::Info);
+}
+
+#[test]
+fn sets_localconfig_launch_options() {
+	let localconfig_str = "\"UserLocalConfigStore\"\n{\n\t\"Software\"\n\t{\n\t\t\"Valve\"\n\t\t{\n\t\t\t\"Steam\"\n\t\t\t{\n\t\t\t\t\"apps\"\n\t\t\t\t{\n\t\t\t\t\t\"4000\"\n\t\t\t\t\t{\n\t\t\t\t\t}\n\t\t\t\t}\n\t\t\t}\n\t\t}\n\t}\n}\n";
+
+	let new_localconfig_str = launch_options::set_localconfig_launch_options(localconfig_str, 4000, "-novid").unwrap();
+	assert!(new_localconfig_str.contains("\"LaunchOptions\"\t\t\"-novid\""));
+
+	assert!(launch_options::set_localconfig_launch_options(localconfig_str, 220, "-novid").is_err());
+}
@@ -0,0 +1,304 @@
+#![cfg(feature = "patch")]
+
+use std::path::{Path, PathBuf};
+
+use gmodpatchtool::steam::{self, AppBranch, LinuxRuntime, SteamInstallation, SteamLibrary, SteamUser};
+
+// Fake Steam trees under tests/fixtures/steam
+// Library paths in their libraryfolders.vdf are relative to the crate root, which is where cargo runs tests
+fn fixture(path: &str) -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("steam").join(path)
+}
+
+fn fixture_installation(path: &str) -> SteamInstallation {
+	SteamInstallation::new(fixture(path).canonicalize().unwrap())
+}
+
+fn user(steam_id_64: &str, account_name: &str, most_recent: bool, timestamp: u64) -> SteamUser {
+	SteamUser {
+		steam_id_64: steam_id_64.to_string(),
+		account_name: account_name.to_string(),
+		persona_name: account_name.to_string(),
+		most_recent,
+		timestamp
+	}
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn finds_snap_installation() {
+	let steam_installations = steam::find_installations_in(&fixture("snap/home"), None);
+
+	assert_eq!(steam_installations, vec![fixture_installation("snap/home/snap/steam/common/.local/share/Steam")]);
+	assert!(steam_installations[0].has_gmod());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn finds_flatpak_installation() {
+	let steam_installations = steam::find_installations_in(&fixture("flatpak/home"), None);
+
+	assert_eq!(steam_installations, vec![fixture_installation("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam")]);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn dedupes_symlinked_installations() {
+	// ~/.steam/steam is a symlink to $XDG_DATA_HOME/Steam
+	let home_dir = fixture("capitalized/home");
+	let steam_installations = steam::find_installations_in(&home_dir, Some(&home_dir.join(".local").join("share")));
+
+	assert_eq!(steam_installations, vec![fixture_installation("capitalized/home/.local/share/Steam")]);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn finds_nothing_in_empty_home() {
+	assert!(steam::find_installations_in(&fixture("snap"), None).is_empty());
+}
+
+#[test]
+fn reads_users() {
+	let steam_users = fixture_installation("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam").read_users().unwrap();
+
+	assert_eq!(steam_users, vec![
+		SteamUser {
+			steam_id_64: "76561198000000002".to_string(),
+			account_name: "bob".to_string(),
+			persona_name: "Bob".to_string(),
+			most_recent: true,
+			timestamp: 1754956800
+		},
+		SteamUser {
+			steam_id_64: "76561198000000003".to_string(),
+			account_name: "Carol".to_string(),
+			persona_name: "Carol".to_string(),
+			most_recent: false,
+			timestamp: 1754870400
+		}
+	]);
+}
+
+#[test]
+fn reads_libraries_and_skips_missing_ones() {
+	let steam_libraries = fixture_installation("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam").read_libraries().unwrap();
+
+	// The unplugged library doesn't exist, so it's left out
+	assert_eq!(steam_libraries.len(), 2);
+	assert_eq!(steam_libraries[0].path, fixture("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam").canonicalize().unwrap());
+	assert_eq!(steam_libraries[0].app_ids, vec!["228980".to_string()]);
+	assert_eq!(steam_libraries[1].path, fixture("flatpak/library").canonicalize().unwrap());
+	assert_eq!(steam_libraries[1].app_ids, vec!["4000".to_string()]);
+}
+
+#[test]
+fn finds_gmod_in_every_library() {
+	let gmod_installs = fixture_installation("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam").find_gmod_installs().unwrap();
+
+	assert_eq!(gmod_installs.len(), 2);
+
+	// Leftover manifest Steam doesn't list anymore, without a GarrysMod directory
+	assert!(!gmod_installs[0].listed_by_steam);
+	assert_eq!(gmod_installs[0].manifest.build_id, 18000000);
+	assert_eq!(gmod_installs[0].find_install_dir(), None);
+
+	assert!(gmod_installs[1].listed_by_steam);
+	assert_eq!(gmod_installs[1].manifest.install_dir, "GarrysMod");
+	assert_eq!(gmod_installs[1].manifest.state_flags, 4);
+	assert_eq!(gmod_installs[1].manifest.build_id, 19283746);
+	assert_eq!(gmod_installs[1].manifest.last_owner, 76561198000000003);
+	assert_eq!(gmod_installs[1].manifest.mounted_config.beta_key.as_deref(), Some("x86-64"));
+	assert_eq!(gmod_installs[1].manifest.installed_depots.keys().collect::<Vec<_>>(), ["4001"]);
+	assert_eq!(gmod_installs[1].manifest.installed_depots["4001"].manifest, "2345678901234567890");
+	assert_eq!(gmod_installs[1].manifest.installed_depots["4001"].size, 3221225472);
+	assert_eq!(gmod_installs[1].find_install_dir(), Some(fixture("flatpak/library/steamapps/common/GarrysMod").canonicalize().unwrap()));
+}
+
+#[test]
+fn reads_capitalized_steamapps() {
+	let steam_installation = fixture_installation("capitalized/home/.local/share/Steam");
+	let gmod_installs = steam_installation.find_gmod_installs().unwrap();
+
+	assert_eq!(gmod_installs.len(), 1);
+	assert!(gmod_installs[0].listed_by_steam);
+	assert_eq!(gmod_installs[0].manifest.mounted_config.beta_key, None);
+	assert_eq!(gmod_installs[0].find_install_dir(), Some(fixture("capitalized/home/.local/share/Steam/SteamApps/common/GarrysMod").canonicalize().unwrap()));
+}
+
+#[test]
+fn reads_compat_tool() {
+	let steam_installation = fixture_installation("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam");
+
+	assert_eq!(steam_installation.read_compat_tool(steam::GMOD_APP_ID).unwrap().as_deref(), Some("proton_experimental"));
+	assert_eq!(steam_installation.read_compat_tool(228980).unwrap(), None);
+	assert_eq!(steam_installation.read_global_compat_tool().unwrap().as_deref(), Some("steamlinuxruntime_sniper"));
+
+	// No global default
+	assert_eq!(fixture_installation("capitalized/home/.local/share/Steam").read_global_compat_tool().unwrap(), None);
+}
+
+#[test]
+fn ignores_global_default_compat_tool() {
+	let steam_installation = fixture_installation("global_default/Steam");
+
+	// Steam only uses it for titles without a native Linux build, which GMod has
+	assert_eq!(steam_installation.read_compat_tool(steam::GMOD_APP_ID).unwrap(), None);
+	assert_eq!(steam_installation.read_global_compat_tool().unwrap().as_deref(), Some("proton_9"));
+
+	// Turned off for this app
+	assert_eq!(steam_installation.read_compat_tool(220).unwrap(), None);
+}
+
+#[test]
+fn reports_missing_files() {
+	let steam_installation = fixture_installation("missing/home/.steam/steam");
+
+	assert!(steam_installation.read_users().unwrap_err().contains("loginusers.vdf"));
+	assert!(steam_installation.read_libraries().unwrap_err().contains("libraryfolders.vdf"));
+	assert!(steam_installation.find_gmod_installs().is_err());
+	assert!(steam_installation.read_compat_tool(steam::GMOD_APP_ID).unwrap_err().contains("config.vdf"));
+	assert!(!steam_installation.has_gmod());
+
+	// Users, but no config.vdf
+	assert!(fixture_installation("snap/home/snap/steam/common/.local/share/Steam").read_compat_tool(steam::GMOD_APP_ID).is_err());
+}
+
+#[test]
+fn selects_user_from_argument() {
+	let steam_users = vec![user("76561198000000002", "bob", true, 2), user("76561198000000003", "Carol", false, 1)];
+
+	assert_eq!(steam::select_user(&steam_users, Some("76561198000000003"), 0), Ok(1));
+	assert_eq!(steam::select_user(&steam_users, Some(" carol "), 76561198000000002), Ok(1));
+	assert!(steam::select_user(&steam_users, Some("dave"), 0).unwrap_err().contains("76561198000000003"));
+}
+
+#[test]
+fn selects_app_owner_then_most_recent_user() {
+	let steam_users = vec![user("76561198000000002", "bob", false, 1), user("76561198000000003", "Carol", true, 2)];
+
+	assert_eq!(steam::select_user(&steam_users, None, 76561198000000002), Ok(0));
+	// Owner isn't signed in here
+	assert_eq!(steam::select_user(&steam_users, None, 76561198000000009), Ok(1));
+	assert_eq!(steam::select_user(&steam_users, None, 0), Ok(1));
+	assert!(steam::select_user(&[], None, 0).is_err());
+}
+
+#[test]
+fn finds_builtin_proton() {
+	let compat_tool = fixture_installation("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam").find_compat_tool_in("Proton_Experimental", &fixture("flatpak"));
+
+	assert_eq!(compat_tool.name, "proton_experimental");
+	assert_eq!(compat_tool.path, Some(fixture("flatpak/library/steamapps/common/Proton - Experimental").canonicalize().unwrap()));
+	assert_eq!(compat_tool.version.as_deref(), Some("experimental-9.0-20240806"));
+	assert_eq!(compat_tool.major_version(), Some(9));
+	assert!(compat_tool.is_proton());
+
+	let compat_tool = fixture_installation("snap/home/snap/steam/common/.local/share/Steam").find_compat_tool_in("proton_9", &fixture("snap"));
+
+	assert_eq!(compat_tool.path, Some(fixture("snap/home/snap/steam/common/.local/share/Steam/steamapps/common/Proton 9.0 (Beta)").canonicalize().unwrap()));
+	assert_eq!(compat_tool.version.as_deref(), Some("proton-9.0-2"));
+}
+
+#[test]
+fn finds_custom_and_runtime_compat_tools() {
+	let steam_installation = fixture_installation("capitalized/home/.local/share/Steam");
+	// Instead of /, so nothing installed on this machine gets found
+	let root_dir = fixture("capitalized");
+
+	let compat_tool = steam_installation.find_compat_tool_in("ge-proton9-20", &root_dir);
+	assert_eq!(compat_tool.path, Some(fixture("capitalized/home/.local/share/Steam/compatibilitytools.d/GE-Proton9-20").canonicalize().unwrap()));
+	assert_eq!(compat_tool.major_version(), Some(9));
+
+	// System-wide, from a distro package
+	let compat_tool = steam_installation.find_compat_tool_in("proton-cachyos", &root_dir);
+	assert_eq!(compat_tool.path, Some(fixture("capitalized/usr/share/steam/compatibilitytools.d/proton-cachyos").canonicalize().unwrap()));
+	assert_eq!(compat_tool.version.as_deref(), Some("proton-cachyos-10.0-20250807"));
+
+	let compat_tool = steam_installation.find_compat_tool_in("steamlinuxruntime_sniper", &root_dir);
+	assert_eq!(compat_tool.path, Some(fixture("capitalized/home/.local/share/Steam/SteamApps/common/SteamLinuxRuntime_sniper").canonicalize().unwrap()));
+	assert!(!compat_tool.is_proton());
+
+	let compat_tool = steam_installation.find_compat_tool_in("proton_8", &root_dir);
+	assert_eq!(compat_tool.path, None);
+	assert_eq!(compat_tool.version, None);
+}
+
+#[test]
+fn reads_compat_prefixes() {
+	// Set up by Proton 7, but GMod uses Proton 9 now
+	let compat_prefix = SteamLibrary::new(fixture("flatpak/library").canonicalize().unwrap()).read_compat_prefix(steam::GMOD_APP_ID).unwrap();
+
+	assert_eq!(compat_prefix.path, fixture("flatpak/library/steamapps/compatdata/4000/pfx").canonicalize().unwrap());
+	assert!(compat_prefix.initialized);
+	assert_eq!(compat_prefix.version.as_deref(), Some("7.0-6"));
+	assert_eq!(compat_prefix.major_version(), Some(7));
+
+	// Proton was interrupted while setting it up
+	let compat_prefix = SteamLibrary::new(fixture("snap/home/snap/steam/common/.local/share/Steam").canonicalize().unwrap()).read_compat_prefix(steam::GMOD_APP_ID).unwrap();
+	assert!(!compat_prefix.initialized);
+
+	// Never launched with Proton
+	assert_eq!(SteamLibrary::new(fixture("capitalized/home/.local/share/Steam").canonicalize().unwrap()).read_compat_prefix(steam::GMOD_APP_ID), None);
+}
+
+#[test]
+fn recognizes_linux_runtimes() {
+	let steam_installation = fixture_installation("capitalized/home/.local/share/Steam");
+	let compat_tool = steam_installation.find_compat_tool_in(&steam_installation.read_compat_tool(steam::GMOD_APP_ID).unwrap().unwrap(), &fixture("capitalized"));
+
+	assert_eq!(compat_tool.linux_runtime(), Some(LinuxRuntime::Sniper));
+	assert_eq!(compat_tool.version.as_deref(), Some("0.20240806.99221"));
+
+	assert_eq!(LinuxRuntime::from_compat_tool_name("SteamLinuxRuntime"), Some(LinuxRuntime::Scout));
+	assert_eq!(LinuxRuntime::from_compat_tool_name("steamlinuxruntime_soldier"), Some(LinuxRuntime::Soldier));
+	assert_eq!(LinuxRuntime::from_compat_tool_name("steamlinuxruntime_medic"), Some(LinuxRuntime::Unknown));
+	assert_eq!(LinuxRuntime::from_compat_tool_name("proton_9"), None);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn prefers_registry_steam_path() {
+	let home_dir = fixture("registry/home");
+	let steam_installations = steam::find_installations_in(&home_dir, Some(&home_dir.join(".local").join("share")));
+
+	assert_eq!(steam_installations, vec![fixture_installation("registry/opt/steam"), fixture_installation("registry/home/.local/share/Steam")]);
+}
+
+#[test]
+fn reads_registry() {
+	let steam_registry = steam::read_registry_in(&fixture("registry/home")).unwrap();
+
+	assert_eq!(steam_registry.steam_path, Some(fixture("registry/opt/steam").canonicalize().unwrap()));
+	assert_eq!(steam_registry.source_mod_install_path, Some(fixture("registry/opt/steam/steamapps/sourcemods").canonicalize().unwrap()));
+	assert_eq!(steam_registry.running_app_id, steam::GMOD_APP_ID);
+	assert_eq!(steam_registry.pid, Some(4194305));
+
+	// steam.pid is left over from a Steam that isn't running anymore, so RunningAppID can't be trusted
+	assert!(!steam_registry.is_steam_running());
+	assert_eq!(steam_registry.is_app_running(steam::GMOD_APP_ID), None);
+
+	// Flatpak's steam.pid is from inside its sandbox
+	assert_eq!(steam::read_registry_in(&fixture("flatpak/home")).unwrap().pid, None);
+
+	assert_eq!(steam::read_registry_in(&fixture("snap/home")), None);
+}
+
+#[test]
+fn reads_app_branches() {
+	let steam_installation = fixture_installation("flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam");
+	let gmod_branches = steam_installation.read_app_branches(steam::GMOD_APP_ID).unwrap();
+
+	assert_eq!(gmod_branches.iter().map(|gmod_branch| gmod_branch.name.as_str()).collect::<Vec<_>>(), ["public", "x86-64", "dev", "prerelease", "sdk"]);
+	assert_eq!(gmod_branches[1], AppBranch {
+		name: "x86-64".to_string(),
+		build_id: 19283746,
+		description: Some("Chromium + 64-bit binaries".to_string()),
+		password_required: false,
+		time_updated: Some(1754870400)
+	});
+	assert!(gmod_branches[4].password_required);
+
+	// Not in Steam's cache
+	assert_eq!(steam_installation.read_app_branches(4020).unwrap(), vec![]);
+	assert_eq!(fixture_installation("snap/home/snap/steam/common/.local/share/Steam").read_app_branches(steam::GMOD_APP_ID).unwrap(), vec![]);
+}
@@ -0,0 +1,241 @@
+#![cfg(feature = "patch")]
+
+use std::path::{Path, PathBuf};
+
+use gmodpatchtool::vdf::{self, Document};
+use serde::Deserialize;
+
+// Samples of Steam's files under tests/fixtures/vdf, laid out like Steam writes them
+fn fixture(path: &str) -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(path)
+}
+
+fn read_fixture(path: &str) -> String {
+	std::fs::read_to_string(fixture(path)).unwrap()
+}
+
+const LAUNCH_OPTIONS_PATH: [&str; 7] = ["UserLocalConfigStore", "Software", "Valve", "Steam", "apps", "4000", "LaunchOptions"];
+
+#[test]
+fn round_trips_unchanged() {
+	for path in [
+		"vdf/localconfig.vdf",
+		"vdf/config.vdf",
+		"vdf/appmanifest_4000.acf",
+		"steam/capitalized/home/.local/share/Steam/userdata/39734276/config/localconfig.vdf",
+		"steam/flatpak/home/.var/app/com.valvesoftware.Steam/.local/share/Steam/config/config.vdf",
+		"steam/snap/home/snap/steam/common/.local/share/Steam/steamapps/appmanifest_4000.acf",
+		"steam/registry/home/.steam/registry.vdf"
+	] {
+		let vdf_str = read_fixture(path);
+
+		assert_eq!(Document::parse(&vdf_str).unwrap().to_string(), vdf_str, "{path}");
+	}
+}
+
+#[test]
+fn round_trips_unusual_layouts() {
+	for vdf_str in [
+		"\"a\"\r\n{\r\n\t\"b\"\t\"c\"\r\n}\r\n",
+		"a { b c d { } }",
+		"\"a\" { \"b\" \"1\" [$WIN32] \"b\" \"2\" [$POSIX] }",
+		"// Comment\n\"a\"\n{\n\t\"b\"\t\t\"\"\n\t// Another\n}",
+		""
+	] {
+		assert_eq!(Document::parse(vdf_str).unwrap().to_string(), vdf_str);
+	}
+}
+
+#[test]
+fn gets_values_case_insensitively() {
+	let localconfig = Document::parse(&read_fixture("vdf/localconfig.vdf")).unwrap();
+
+	assert_eq!(localconfig.get(&["userlocalconfigstore", "software", "valve", "steam", "Apps", "220", "launchoptions"]), Some("-novid"));
+	assert_eq!(localconfig.get(&["UserLocalConfigStore", "friends", "PersonaName"]), Some("Dave \"The Cat\" Smith"));
+	assert_eq!(localconfig.get(&["UserLocalConfigStore", "WebStorage", "path"]), Some("C:\\Program Files (x86)\\Steam"));
+	assert_eq!(localconfig.get(&LAUNCH_OPTIONS_PATH), None);
+
+	// Sections aren't values
+	assert_eq!(localconfig.get(&["UserLocalConfigStore", "Software"]), None);
+	assert!(localconfig.has_section(&["UserLocalConfigStore", "Software", "Valve", "Steam", "apps", "4000"]));
+
+	let app_manifest = Document::parse(&read_fixture("vdf/appmanifest_4000.acf")).unwrap();
+
+	assert_eq!(app_manifest.keys(&["AppState", "InstalledDepots"]), Some(vec!["4001", "4002"]));
+	assert_eq!(app_manifest.get(&["AppState", "MountedConfig", "BetaKey"]), Some("x86-64"));
+}
+
+#[test]
+fn sets_existing_value_in_place() {
+	let config_str = read_fixture("vdf/config.vdf");
+	let mut config = Document::parse(&config_str).unwrap();
+
+	config.set(&["InstallConfigStore", "Software", "Valve", "Steam", "CompatToolMapping", "0", "name"], "proton_experimental").unwrap();
+
+	assert_eq!(config.to_string(), config_str.replace("\"proton_9\"", "\"proton_experimental\""));
+}
+
+#[test]
+fn adds_missing_value_like_steam() {
+	let localconfig_str = read_fixture("vdf/localconfig.vdf");
+	let mut localconfig = Document::parse(&localconfig_str).unwrap();
+
+	localconfig.set(&LAUNCH_OPTIONS_PATH, "GMOD_ENABLE_LD_PRELOAD=1 \"%command%\"").unwrap();
+
+	let expected_str = localconfig_str.replace(
+		"\t\t\t\t\t\t\t\"lastexit\"\t\t\"1754960400\"\n\t\t\t\t\t\t}\n",
+		"\t\t\t\t\t\t\t\"lastexit\"\t\t\"1754960400\"\n\t\t\t\t\t\t}\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"GMOD_ENABLE_LD_PRELOAD=1 \\\"%command%\\\"\"\n"
+	);
+
+	assert_eq!(localconfig.to_string(), expected_str);
+	assert_eq!(Document::parse(&expected_str).unwrap().get(&LAUNCH_OPTIONS_PATH), Some("GMOD_ENABLE_LD_PRELOAD=1 \"%command%\""));
+}
+
+#[test]
+fn adds_missing_sections_like_steam() {
+	let app_manifest_str = read_fixture("vdf/appmanifest_4000.acf");
+	let mut app_manifest = Document::parse(&app_manifest_str).unwrap();
+
+	app_manifest.set(&["AppState", "UserConfig", "BetaKey"], "public").unwrap();
+	app_manifest.set(&["AppState", "StagedDepots", "4001", "manifest"], "1").unwrap();
+
+	let expected_str = app_manifest_str
+		.replace("\t\t\"BetaKey\"\t\t\"x86-64\"\n\t}\n\t\"MountedConfig\"", "\t\t\"BetaKey\"\t\t\"public\"\n\t}\n\t\"MountedConfig\"")
+		.replace("\t}\n}\n", "\t}\n\t\"StagedDepots\"\n\t{\n\t\t\"4001\"\n\t\t{\n\t\t\t\"manifest\"\t\t\"1\"\n\t\t}\n\t}\n}\n");
+
+	assert_eq!(app_manifest.to_string(), expected_str);
+}
+
+#[test]
+fn refuses_to_mix_values_and_sections() {
+	let mut localconfig = Document::parse(&read_fixture("vdf/localconfig.vdf")).unwrap();
+
+	assert!(localconfig.set(&["UserLocalConfigStore", "Software"], "1").is_err());
+	assert!(localconfig.set(&["UserLocalConfigStore", "friends", "PersonaName", "name"], "Dave").is_err());
+}
+
+#[test]
+fn removes_value_and_its_line() {
+	let localconfig_str = read_fixture("vdf/localconfig.vdf");
+	let mut localconfig = Document::parse(&localconfig_str).unwrap();
+
+	assert!(localconfig.remove(&["UserLocalConfigStore", "Software", "Valve", "Steam", "apps", "220", "LaunchOptions"]));
+	assert!(!localconfig.remove(&LAUNCH_OPTIONS_PATH));
+
+	assert_eq!(localconfig.to_string(), localconfig_str.replace("\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"-novid\"", ""));
+}
+
+#[test]
+fn reports_syntax_errors() {
+	for vdf_str in ["\"a\" {", "\"a\" { } }", "\"a\" \"b", "\"a\"", "{ }"] {
+		assert!(Document::parse(vdf_str).is_err(), "{vdf_str}");
+	}
+}
+
+// Just the path to GMod's LaunchOptions, so everything else in localconfig.vdf is skipped
+#[derive(Deserialize)]
+struct LocalConfig {
+	#[serde(rename = "Software")]
+	software: LocalConfigSoftware
+}
+
+#[derive(Deserialize)]
+struct LocalConfigSoftware {
+	#[serde(rename = "Valve")]
+	valve: LocalConfigValve
+}
+
+#[derive(Deserialize)]
+struct LocalConfigValve {
+	#[serde(rename = "Steam")]
+	steam: LocalConfigSteam
+}
+
+#[derive(Deserialize)]
+struct LocalConfigSteam {
+	apps: std::collections::HashMap<String, LocalConfigApp>
+}
+
+#[derive(Deserialize)]
+struct LocalConfigApp {
+	#[serde(rename = "LastPlayed")]
+	last_played: u64,
+	#[serde(rename = "LaunchOptions")]
+	launch_options: Option<String>
+}
+
+#[test]
+fn deserializes_only_what_it_needs() {
+	let localconfig_str = read_fixture("vdf/localconfig.vdf");
+	let (localconfig, key) = vdf::from_str_with_key::<LocalConfig>(&localconfig_str).unwrap();
+
+	assert_eq!(key, "UserLocalConfigStore");
+	assert_eq!(localconfig.software.valve.steam.apps["220"].launch_options.as_deref(), Some("-novid"));
+	assert_eq!(localconfig.software.valve.steam.apps["4000"].last_played, 1754956800);
+	assert_eq!(localconfig.software.valve.steam.apps["4000"].launch_options, None);
+
+	assert!(vdf::from_str::<LocalConfig>("\"UserLocalConfigStore\" { \"Software\" { } }").is_err());
+	assert!(vdf::from_str::<LocalConfig>(&(localconfig_str + "\"Extra\" { }")).is_err());
+}
+
+// Far deeper than any recursive parser could go on a 2 MiB test thread
+#[test]
+fn handles_deep_nesting_without_recursion() {
+	let depth = 100_000;
+	let localconfig_str = read_fixture("vdf/localconfig.vdf").replace(
+		"\t\"WebStorage\"\n\t{\n",
+		&format!("\t\"WebStorage\"\n\t{{\n{}{}\n", "\"a\"{".repeat(depth), "}".repeat(depth))
+	);
+
+	let localconfig: LocalConfig = vdf::from_str(&localconfig_str).unwrap();
+	assert_eq!(localconfig.software.valve.steam.apps["220"].launch_options.as_deref(), Some("-novid"));
+
+	let document = Document::parse(&localconfig_str).unwrap();
+	assert_eq!(document.get(&["UserLocalConfigStore", "WebStorage", "path"]), Some("C:\\Program Files (x86)\\Steam"));
+	assert_eq!(document.to_string(), localconfig_str);
+}
+
+#[test]
+fn reads_app_info_versions() {
+	for path in ["vdf/appinfo_v27.vdf", "vdf/appinfo_v28.vdf", "vdf/appinfo_v29.vdf"] {
+		let app_info_bytes = std::fs::read(fixture(path)).unwrap();
+		let gmod_app_info = vdf::read_app_info(&app_info_bytes, 4000).unwrap().unwrap();
+
+		assert_eq!(gmod_app_info.app_id, 4000, "{path}");
+		assert_eq!(gmod_app_info.change_number, 24000000, "{path}");
+		assert_eq!(gmod_app_info.data.get_path(&["appinfo", "appid"]).and_then(vdf::BinaryValue::as_u64), Some(4000), "{path}");
+		assert_eq!(gmod_app_info.data.get_path(&["appinfo", "common", "name"]).and_then(vdf::BinaryValue::as_str), Some("Garry's Mod"), "{path}");
+		assert_eq!(gmod_app_info.data.get_path(&["appinfo", "depots", "branches", "x86-64", "BuildID"]).and_then(vdf::BinaryValue::as_u64), Some(19283746), "{path}");
+		assert_eq!(gmod_app_info.data.get_path(&["appinfo", "config", "launch", "0", "executable"]).and_then(vdf::BinaryValue::as_str), Some("hl2.exe"), "{path}");
+
+		let half_life_2_app_info = vdf::read_app_info(&app_info_bytes, 220).unwrap().unwrap();
+		assert_eq!(half_life_2_app_info.data.get_path(&["appinfo", "common", "name"]).and_then(vdf::BinaryValue::as_str), Some("Half-Life 2"), "{path}");
+
+		assert!(vdf::read_app_info(&app_info_bytes, 4020).unwrap().is_none(), "{path}");
+	}
+}
+
+#[test]
+fn rejects_bad_app_info() {
+	let app_info_bytes = std::fs::read(fixture("vdf/appinfo_v28.vdf")).unwrap();
+
+	// Future versions, and files cut off partway through
+	let mut future_app_info_bytes = app_info_bytes.clone();
+	future_app_info_bytes[0] = 0x2A;
+
+	assert!(matches!(vdf::read_app_info(&future_app_info_bytes, 4000), Err(vdf::Error::UnsupportedVersion(0x0756442A))));
+	assert!(vdf::read_app_info(&app_info_bytes[..app_info_bytes.len() - 40], 4000).is_err());
+	assert!(vdf::read_app_info(&[], 4000).is_err());
+}
+
+#[test]
+fn reads_shortcuts() {
+	let shortcuts = vdf::from_binary_slice(&std::fs::read(fixture("vdf/shortcuts.vdf")).unwrap()).unwrap();
+	let shortcut = shortcuts.get_path(&["shortcuts", "0"]).and_then(vdf::BinaryValue::as_section).unwrap();
+
+	assert!(matches!(shortcut.get("appid"), Some(vdf::BinaryValue::Int32(-1234567890))));
+	assert_eq!(shortcut.get("AppName").and_then(vdf::BinaryValue::as_str), Some("GModPatchTool"));
+	assert_eq!(shortcut.get("LaunchOptions").and_then(vdf::BinaryValue::as_str), Some("--launch-gmod"));
+	assert_eq!(shortcut.get("LastPlayTime").and_then(vdf::BinaryValue::as_u64), Some(1754870400));
+	assert_eq!(shortcut.iter().map(|(key, _)| key).collect::<Vec<_>>(), ["appid", "AppName", "Exe", "StartDir", "LaunchOptions", "LastPlayTime", "tags"]);
+}